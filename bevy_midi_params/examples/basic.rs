@@ -46,6 +46,8 @@ impl Default for GameSettings {
     }
 }
 
+// Deliberately shows the legacy plugin
+#[allow(deprecated)]
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
@@ -48,11 +48,11 @@ fn main() {
     // #[cfg(feature = "midi")]
     // app.add_plugins(MidiControlPlugin::default().with_controller("MIDI Fighter"));
     
-    app.add_systems(Update, (
-        demo_system,
-        #[cfg(feature = "ui")]
-        ui_system,
-    ));
+    app.add_systems(Update, demo_system);
+
+    #[cfg(feature = "ui")]
+    app.add_plugins(bevy_egui::EguiPlugin::default())
+        .add_systems(bevy_egui::EguiPrimaryContextPass, ui_system);
     
     app.run();
 }
@@ -79,10 +79,15 @@ fn demo_system(settings: Res<GameSettings>) {
 fn ui_system(
     mut contexts: bevy_egui::EguiContexts,
     mut settings: ResMut<GameSettings>,
+    defaults: Local<GameSettings>,
 ) {
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
     bevy_egui::egui::Window::new("Game Settings")
-        .show(contexts.ctx_mut(), |ui| {
-            settings.render_ui(ui);
+        .show(ctx, |ui| {
+            settings.render_ui(ui, &defaults);
         });
 }
 
@@ -12,7 +12,6 @@
 
 use bevy::prelude::*;
 use bevy_midi_params::prelude::*;
-use std::f32::consts::PI;
 
 #[derive(Resource, MidiParams)]
 struct PhysicsParams {
@@ -31,8 +30,9 @@ struct PhysicsParams {
     #[midi(33, button)]
     pub paused: bool,
     
-    #[midi(34, button)]
-    pub reset_scene: bool,
+    // Fires MidiTriggered<PhysicsParams> instead of holding a value
+    #[midi(34, trigger)]
+    pub reset_scene: (),
 }
 
 impl Default for PhysicsParams {
@@ -43,7 +43,7 @@ impl Default for PhysicsParams {
             bounce_damping: 0.8,
             time_scale: 1.0,
             paused: false,
-            reset_scene: false,
+            reset_scene: (),
         }
     }
 }
@@ -51,7 +51,6 @@ impl Default for PhysicsParams {
 #[derive(Component)]
 struct PhysicsObject {
     velocity: Vec3,
-    mass: f32,
 }
 
 #[derive(Component)]
@@ -60,7 +59,7 @@ struct Bouncy;
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
-        .add_plugins(MidiParamsPlugin::new("physics_playground.ron"))
+        .add_plugins(bevy_midi_params::dev_plugins_with_file("physics_playground.ron"))
         .add_systems(Startup, setup_physics_scene)
         .add_systems(Update, (
             physics_simulation,
@@ -76,45 +75,41 @@ fn setup_physics_scene(
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
     // Ground plane
-    commands.spawn(PbrBundle {
-        mesh: meshes.add(Plane3d::default().mesh().size(20.0, 20.0)),
-        material: materials.add(StandardMaterial {
-            base_color: Color::rgb(0.3, 0.5, 0.3),
+    commands.spawn((
+        Mesh3d(meshes.add(Plane3d::default().mesh().size(20.0, 20.0))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::srgb(0.3, 0.5, 0.3),
             ..default()
-        }),
-        transform: Transform::from_xyz(0.0, 0.0, 0.0),
-        ..default()
-    });
+        })),
+        Transform::from_xyz(0.0, 0.0, 0.0),
+    ));
 
     // Spawn some initial physics objects
     spawn_physics_objects(&mut commands, &mut meshes, &mut materials, 5);
 
     // Camera
-    commands.spawn(Camera3dBundle {
-        transform: Transform::from_xyz(0.0, 10.0, 15.0).looking_at(Vec3::new(0.0, 5.0, 0.0), Vec3::Y),
-        ..default()
-    });
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 10.0, 15.0).looking_at(Vec3::new(0.0, 5.0, 0.0), Vec3::Y),
+    ));
 
     // Light
-    commands.spawn(DirectionalLightBundle {
-        directional_light: DirectionalLight {
+    commands.spawn((
+        DirectionalLight {
             illuminance: 3000.0,
             shadows_enabled: true,
             ..default()
         },
-        transform: Transform::from_rotation(Quat::from_euler(EulerRot::ZYX, 0.0, 1.0, -0.5)),
-        ..default()
-    });
+        Transform::from_rotation(Quat::from_euler(EulerRot::ZYX, 0.0, 1.0, -0.5)),
+    ));
 }
 
 fn spawn_physics_objects(
     commands: &mut Commands,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<StandardMaterial>>,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
     count: usize,
 ) {
-    use bevy::math::*;
-    
     for i in 0..count {
         let x = (i as f32 - count as f32 / 2.0) * 2.0;
         let y = 10.0 + i as f32 * 2.0;
@@ -122,22 +117,18 @@ fn spawn_physics_objects(
         let color = Color::hsl(i as f32 * 60.0, 0.8, 0.6);
         
         commands.spawn((
-            PbrBundle {
-                mesh: meshes.add(Sphere::new(0.5)),
-                material: materials.add(StandardMaterial {
-                    base_color: color,
-                    ..default()
-                }),
-                transform: Transform::from_xyz(x, y, 0.0),
+            Mesh3d(meshes.add(Sphere::new(0.5))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: color,
                 ..default()
-            },
+            })),
+            Transform::from_xyz(x, y, 0.0),
             PhysicsObject {
                 velocity: Vec3::new(
                     (i as f32 - count as f32 / 2.0) * 0.5,
                     0.0,
                     0.0,
                 ),
-                mass: 1.0,
             },
             Bouncy,
         ));
@@ -153,7 +144,7 @@ fn physics_simulation(
         return;
     }
 
-    let dt = time.delta_seconds() * params.time_scale;
+    let dt = time.delta_secs() * params.time_scale;
     let gravity = Vec3::new(0.0, -params.gravity_strength, 0.0);
     
     for (mut transform, mut physics) in physics_query.iter_mut() {
@@ -196,13 +187,13 @@ fn physics_simulation(
 }
 
 fn handle_reset(
-    mut params: ResMut<PhysicsParams>,
+    mut triggers: EventReader<MidiTriggered<PhysicsParams>>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     physics_query: Query<Entity, With<PhysicsObject>>,
 ) {
-    if params.reset_scene {
+    if triggers.read().any(|trigger| trigger.field == "reset_scene") {
         // Remove all physics objects
         for entity in physics_query.iter() {
             commands.entity(entity).despawn();
@@ -211,9 +202,6 @@ fn handle_reset(
         // Spawn new ones
         spawn_physics_objects(&mut commands, &mut meshes, &mut materials, 5);
         
-        // Reset the flag
-        params.reset_scene = false;
-        
         println!("🔄 Scene reset!");
     }
 }
@@ -226,8 +214,7 @@ fn spawn_random_objects(
     physics_query: Query<Entity, With<PhysicsObject>>,
 ) {
     // Spawn a new object every few seconds
-    if time.elapsed_seconds() % 3.0 < 0.016 && physics_query.iter().len() < 10 {
-        use bevy::math::*;
+    if time.elapsed_secs() % 3.0 < 0.016 && physics_query.iter().len() < 10 {
         use rand::Rng;
         
         let mut rng = rand::thread_rng();
@@ -236,22 +223,18 @@ fn spawn_random_objects(
         let color = Color::hsl(rng.gen_range(0.0..360.0), 0.8, 0.6);
         
         commands.spawn((
-            PbrBundle {
-                mesh: meshes.add(Sphere::new(0.3)),
-                material: materials.add(StandardMaterial {
-                    base_color: color,
-                    ..default()
-                }),
-                transform: Transform::from_xyz(x, 15.0, z),
+            Mesh3d(meshes.add(Sphere::new(0.3))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: color,
                 ..default()
-            },
+            })),
+            Transform::from_xyz(x, 15.0, z),
             PhysicsObject {
                 velocity: Vec3::new(
                     rng.gen_range(-2.0..2.0),
                     0.0,
                     rng.gen_range(-2.0..2.0),
                 ),
-                mass: 1.0,
             },
             Bouncy,
         ));
@@ -262,9 +245,9 @@ fn spawn_random_objects(
 
 #[cfg(test)]
 mod tests {
-    use super::*;
     use bevy::prelude::*;
     use bevy_midi_params::prelude::*;
+    use bevy_midi_params::{ControlType, MidiControl};
 
     #[derive(Resource, MidiParams, PartialEq)]
     struct TestParams {
@@ -290,39 +273,40 @@ mod tests {
 
     #[test]
     fn test_midi_mappings_generation() {
-        let mappings = TestParams::get_midi_mappings();
+        let mappings = TestParams::get_param_mappings();
         assert_eq!(mappings.len(), 3);
         
         // Check range mapping
-        let range_mapping = mappings.iter().find(|m| m.cc == 1).unwrap();
+        let range_mapping = mappings.iter().find(|m| m.control == Some(MidiControl::Cc(1))).unwrap();
         assert_eq!(range_mapping.field_name, "value_a");
         assert_eq!(range_mapping.min_value, 0.0);
         assert_eq!(range_mapping.max_value, 1.0);
         
         // Check button mapping
-        let button_mapping = mappings.iter().find(|m| m.cc == 33).unwrap();
+        let button_mapping = mappings.iter().find(|m| m.control == Some(MidiControl::Cc(33))).unwrap();
         assert_eq!(button_mapping.field_name, "flag");
-        assert!(matches!(button_mapping.control_type, crate::ControlType::Button));
+        assert!(matches!(button_mapping.control_type, ControlType::Button));
     }
 
+    #[cfg(feature = "midi")]
     #[test]
     fn test_midi_value_updates() {
         let mut params = TestParams::default();
         
         // Test range update
-        let changed = params.update_from_midi(1, 0.5); // Normalized value
+        let changed = params.update_from_midi("value_a", 0.25); // Scaled value
         assert!(changed);
-        assert_eq!(params.value_a, 0.5);
+        assert_eq!(params.value_a, 0.25);
         
         // Test button toggle
-        let changed = params.update_from_midi(33, 0.8); // > 0.5 should toggle
+        let changed = params.update_from_midi("flag", 0.8); // > 0.5 should toggle
         assert!(changed);
-        assert_eq!(params.flag, true);
+        assert!(params.flag);
         
         // Test button not triggering on low value
-        let changed = params.update_from_midi(33, 0.3);
+        let changed = params.update_from_midi("flag", 0.3);
         assert!(!changed);
-        assert_eq!(params.flag, true); // Should remain true
+        assert!(params.flag); // Should remain true
     }
 
     #[test]
@@ -339,19 +323,23 @@ mod tests {
         assert!(persist_data.values.contains_key("flag"));
         
         let mut loaded_params = TestParams::default();
-        loaded_params.from_persist_data(&persist_data);
+        loaded_params.apply_persist_data(&persist_data).unwrap();
         
         assert_eq!(loaded_params.value_a, 0.75);
         assert_eq!(loaded_params.value_b, 5.0);
-        assert_eq!(loaded_params.flag, true);
+        assert!(loaded_params.flag);
     }
 
+    #[cfg(feature = "midi")]
     #[test]
     fn test_plugin_setup() {
         let mut app = App::new();
-        app.add_plugins(MidiParamsPlugin::new("test.ron").no_auto_connect());
+        app.add_plugins((
+            ParamsPersistencePlugin::default().with_persist("test.ron"),
+            MidiControlPlugin::new().no_auto_connect(),
+        ));
         
         // Plugin should register the controller resource
-        assert!(app.world.contains_resource::<MidiController>());
+        assert!(app.world().contains_resource::<MidiController>());
     }
 }
@@ -10,6 +10,9 @@
 //! - Knob 4: Normal map strength (0.0 - 2.0)
 //! - Knob 5: Subsurface (0.0 - 1.0)
 //! - Knob 6: Clearcoat (0.0 - 1.0)
+//! - Fader 7: Light intensity (0.0 - 5000.0), 14-bit on controllers that send CC 7 + CC 39
+//! - Knob 8: Light rotation (-180.0 - 180.0)
+//! - Knob 9: Ambient strength (0.0 - 1.0)
 
 use bevy::prelude::*;
 use bevy_midi_params::prelude::*;
@@ -50,7 +53,8 @@ impl Default for MaterialParams {
 
 #[derive(Resource, MidiParams)]
 struct LightingParams {
-    #[midi(7, 0.0..5000.0)]
+    // Wide range, so use the 14-bit pair for 16384 steps instead of 128
+    #[midi(7, hires, 0.0..5000.0)]
     pub light_intensity: f32,
     
     #[midi(8, -180.0..180.0)]
@@ -73,7 +77,7 @@ impl Default for LightingParams {
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
-        .add_plugins(MidiParamsPlugin::new("shader_tweaking.ron"))
+        .add_plugins(bevy_midi_params::dev_plugins_with_file("shader_tweaking.ron"))
         .add_systems(Startup, setup_shader_scene)
         .add_systems(Update, (
             update_materials,
@@ -92,55 +96,48 @@ fn setup_shader_scene(
     
     // Different base materials to test parameters
     let positions = [
-        (Vec3::new(-3.0, 0.0, 0.0), Color::WHITE),      // White sphere
-        (Vec3::new(-1.0, 0.0, 0.0), Color::RED),        // Red sphere
-        (Vec3::new(1.0, 0.0, 0.0), Color::GREEN),       // Green sphere
-        (Vec3::new(3.0, 0.0, 0.0), Color::BLUE),        // Blue sphere
+        (Vec3::new(-3.0, 0.0, 0.0), Color::WHITE),               // White sphere
+        (Vec3::new(-1.0, 0.0, 0.0), Color::srgb(1.0, 0.0, 0.0)), // Red sphere
+        (Vec3::new(1.0, 0.0, 0.0), Color::srgb(0.0, 1.0, 0.0)),  // Green sphere
+        (Vec3::new(3.0, 0.0, 0.0), Color::srgb(0.0, 0.0, 1.0)),  // Blue sphere
     ];
     
     for (position, color) in positions {
         commands.spawn((
-            PbrBundle {
-                mesh: mesh.clone(),
-                material: materials.add(StandardMaterial {
-                    base_color: color,
-                    ..default()
-                }),
-                transform: Transform::from_translation(position),
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: color,
                 ..default()
-            },
+            })),
+            Transform::from_translation(position),
             TweakableMaterial,
         ));
     }
 
     // Ground plane
-    commands.spawn(PbrBundle {
-        mesh: meshes.add(Plane3d::default().mesh().size(10.0, 10.0)),
-        material: materials.add(StandardMaterial {
-            base_color: Color::rgb(0.8, 0.8, 0.8),
+    commands.spawn((
+        Mesh3d(meshes.add(Plane3d::default().mesh().size(10.0, 10.0))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::srgb(0.8, 0.8, 0.8),
             ..default()
-        }),
-        transform: Transform::from_xyz(0.0, -2.0, 0.0),
-        ..default()
-    });
+        })),
+        Transform::from_xyz(0.0, -2.0, 0.0),
+    ));
 
     // Camera
-    commands.spawn(Camera3dBundle {
-        transform: Transform::from_xyz(0.0, 2.0, 8.0).looking_at(Vec3::ZERO, Vec3::Y),
-        ..default()
-    });
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 2.0, 8.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
 
     // Directional light
     commands.spawn((
-        DirectionalLightBundle {
-            directional_light: DirectionalLight {
-                illuminance: 3000.0,
-                shadows_enabled: true,
-                ..default()
-            },
-            transform: Transform::from_rotation(Quat::from_euler(EulerRot::ZYX, 0.0, 1.0, -0.5)),
+        DirectionalLight {
+            illuminance: 3000.0,
+            shadows_enabled: true,
             ..default()
         },
+        Transform::from_rotation(Quat::from_euler(EulerRot::ZYX, 0.0, 1.0, -0.5)),
         TweakableLight,
     ));
 }
@@ -154,7 +151,7 @@ struct TweakableLight;
 fn update_materials(
     params: Res<MaterialParams>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    material_query: Query<&Handle<StandardMaterial>, With<TweakableMaterial>>,
+    material_query: Query<&MeshMaterial3d<StandardMaterial>, With<TweakableMaterial>>,
 ) {
     if !params.is_changed() {
         return;
@@ -166,7 +163,7 @@ fn update_materials(
             material.metallic = params.metallic;
             
             // Apply emission with color based on strength
-            let emission_color = Color::rgb(
+            let emission_color = LinearRgba::rgb(
                 params.emission_strength * 0.8,
                 params.emission_strength * 0.4,
                 params.emission_strength * 0.2,
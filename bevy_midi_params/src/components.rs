@@ -0,0 +1,429 @@
+use bevy::color::{Alpha, Color, Hsla, Hsva, Srgba};
+use bevy::math::{EulerRot, Quat, Rot2, Vec2, Vec3};
+use serde_json::{json, Value};
+
+/// Color space the knobs of a `Color` field move through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorSpace {
+    /// Hue (0-360), saturation and value (0.0-1.0)
+    #[default]
+    Hsv,
+    /// Hue (0-360), saturation and lightness (0.0-1.0)
+    Hsl,
+    /// Red, green and blue (0.0-1.0, sRGB)
+    Rgb,
+}
+
+/// Field types driven by one control per component, e.g. `#[midi(cc = [1, 2, 3])]`
+///
+/// Each component gets its own mapping named `field.component` (e.g. `tint.hue`). `space` only
+/// matters for colors, other types ignore it.
+pub trait ComponentValue: Sized {
+    /// Number of components, the attribute needs exactly one control for each
+    const COUNT: usize;
+
+    /// Name of the component at `index`, as used in mapping names
+    fn component_name(index: usize, space: ColorSpace) -> &'static str;
+
+    /// Range of the component at `index` when the attribute does not give one
+    fn default_range(index: usize, space: ColorSpace) -> (f32, f32);
+
+    /// Current value of the component at `index`
+    fn component(&self, index: usize, space: ColorSpace) -> f32;
+
+    /// Set the component at `index`, returns true if it changed
+    fn set_component(&mut self, index: usize, value: f32, space: ColorSpace) -> bool;
+
+    /// Whether the value pins down the component at `index`, it is not sent back to devices
+    /// otherwise
+    fn is_determined(&self, _index: usize, _space: ColorSpace) -> bool {
+        true
+    }
+
+    /// Readable form stored in the persistence file
+    fn to_persist_value(&self, space: ColorSpace) -> Value;
+
+    /// Parse the form written by `to_persist_value`
+    fn from_persist_value(value: &Value, space: ColorSpace) -> Result<Self, String>;
+
+    /// Index of a named component, None if the type has no such component
+    fn component_index(name: &str, space: ColorSpace) -> Option<usize> {
+        (0..Self::COUNT).find(|&index| Self::component_name(index, space) == name)
+    }
+}
+
+/// Replace `current` with `value`, returns true if it changed
+fn set_if_changed(current: &mut f32, value: f32) -> bool {
+    if (*current - value).abs() > f32::EPSILON {
+        *current = value;
+        true
+    } else {
+        false
+    }
+}
+
+/// Numbers of a persisted `[x, y, ...]` array
+fn persisted_floats<const N: usize>(value: &Value) -> Result<[f32; N], String> {
+    serde_json::from_value::<Vec<f32>>(value.clone())
+        .ok()
+        .and_then(|floats| floats.try_into().ok())
+        .ok_or_else(|| format!("expected an array of {} numbers", N))
+}
+
+impl ComponentValue for Vec2 {
+    const COUNT: usize = 2;
+
+    fn component_name(index: usize, _space: ColorSpace) -> &'static str {
+        ["x", "y"][index]
+    }
+
+    fn default_range(_index: usize, _space: ColorSpace) -> (f32, f32) {
+        (0.0, 1.0)
+    }
+
+    fn component(&self, index: usize, _space: ColorSpace) -> f32 {
+        self[index]
+    }
+
+    fn set_component(&mut self, index: usize, value: f32, _space: ColorSpace) -> bool {
+        set_if_changed(&mut self[index], value)
+    }
+
+    fn to_persist_value(&self, _space: ColorSpace) -> Value {
+        json!([self.x, self.y])
+    }
+
+    fn from_persist_value(value: &Value, _space: ColorSpace) -> Result<Self, String> {
+        persisted_floats(value).map(Vec2::from_array)
+    }
+}
+
+impl ComponentValue for Vec3 {
+    const COUNT: usize = 3;
+
+    fn component_name(index: usize, _space: ColorSpace) -> &'static str {
+        ["x", "y", "z"][index]
+    }
+
+    fn default_range(_index: usize, _space: ColorSpace) -> (f32, f32) {
+        (0.0, 1.0)
+    }
+
+    fn component(&self, index: usize, _space: ColorSpace) -> f32 {
+        self[index]
+    }
+
+    fn set_component(&mut self, index: usize, value: f32, _space: ColorSpace) -> bool {
+        set_if_changed(&mut self[index], value)
+    }
+
+    fn to_persist_value(&self, _space: ColorSpace) -> Value {
+        json!([self.x, self.y, self.z])
+    }
+
+    fn from_persist_value(value: &Value, _space: ColorSpace) -> Result<Self, String> {
+        persisted_floats(value).map(Vec3::from_array)
+    }
+}
+
+/// How close to ±90° a pitch must be for yaw and roll to be treated as locked together
+const GIMBAL_LOCK_DEGREES: f32 = 0.1;
+
+/// Rotation as yaw (around Y), pitch (around X) and roll (around Z) in degrees
+///
+/// Yaw and roll cover -180 to 180, pitch only -90 to 90 as any other pitch is the same
+/// rotation with yaw and roll turned around. At a pitch of ±90° yaw and roll turn around the
+/// same axis (gimbal lock), so only their sum is known and neither is sent back to devices.
+impl ComponentValue for Quat {
+    const COUNT: usize = 3;
+
+    fn component_name(index: usize, _space: ColorSpace) -> &'static str {
+        ["yaw", "pitch", "roll"][index]
+    }
+
+    fn default_range(index: usize, _space: ColorSpace) -> (f32, f32) {
+        if index == 1 {
+            (-90.0, 90.0)
+        } else {
+            (-180.0, 180.0)
+        }
+    }
+
+    fn component(&self, index: usize, _space: ColorSpace) -> f32 {
+        let (yaw, pitch, roll) = self.to_euler(EulerRot::YXZ);
+        [yaw, pitch, roll][index].to_degrees()
+    }
+
+    fn set_component(&mut self, index: usize, value: f32, space: ColorSpace) -> bool {
+        let mut angles = [0, 1, 2].map(|index| self.component(index, space));
+        let value = if index == 1 { value.clamp(-90.0, 90.0) } else { value };
+        if !set_if_changed(&mut angles[index], value) {
+            return false;
+        }
+        let [yaw, pitch, roll] = angles.map(f32::to_radians);
+        *self = Quat::from_euler(EulerRot::YXZ, yaw, pitch, roll);
+        true
+    }
+
+    fn is_determined(&self, index: usize, space: ColorSpace) -> bool {
+        index == 1 || self.component(1, space).abs() < 90.0 - GIMBAL_LOCK_DEGREES
+    }
+
+    fn to_persist_value(&self, _space: ColorSpace) -> Value {
+        let [yaw, pitch, roll] = [0, 1, 2].map(|index| self.component(index, ColorSpace::default()));
+        json!({ "yaw": yaw, "pitch": pitch, "roll": roll })
+    }
+
+    fn from_persist_value(value: &Value, _space: ColorSpace) -> Result<Self, String> {
+        let angle = |name: &str| {
+            value
+                .get(name)
+                .and_then(Value::as_f64)
+                .map(|degrees| (degrees as f32).to_radians())
+                .ok_or_else(|| format!("expected '{}' in degrees", name))
+        };
+        Ok(Quat::from_euler(EulerRot::YXZ, angle("yaw")?, angle("pitch")?, angle("roll")?))
+    }
+}
+
+/// 2D rotation as an angle in degrees
+impl ComponentValue for Rot2 {
+    const COUNT: usize = 1;
+
+    fn component_name(_index: usize, _space: ColorSpace) -> &'static str {
+        "angle"
+    }
+
+    fn default_range(_index: usize, _space: ColorSpace) -> (f32, f32) {
+        (-180.0, 180.0)
+    }
+
+    fn component(&self, _index: usize, _space: ColorSpace) -> f32 {
+        self.as_degrees()
+    }
+
+    fn set_component(&mut self, _index: usize, value: f32, _space: ColorSpace) -> bool {
+        let mut degrees = self.as_degrees();
+        if !set_if_changed(&mut degrees, value) {
+            return false;
+        }
+        *self = Rot2::degrees(degrees);
+        true
+    }
+
+    fn to_persist_value(&self, _space: ColorSpace) -> Value {
+        json!(self.as_degrees())
+    }
+
+    fn from_persist_value(value: &Value, _space: ColorSpace) -> Result<Self, String> {
+        value
+            .as_f64()
+            .map(|degrees| Rot2::degrees(degrees as f32))
+            .ok_or_else(|| "expected an angle in degrees".to_string())
+    }
+}
+
+/// Color in the given space, persisted as its components in that space plus alpha
+///
+/// Knob changes store the color in that space, so the hue survives turning saturation down to 0,
+/// across restarts too.
+impl ComponentValue for Color {
+    const COUNT: usize = 3;
+
+    fn component_name(index: usize, space: ColorSpace) -> &'static str {
+        match space {
+            ColorSpace::Hsv => ["hue", "saturation", "value"][index],
+            ColorSpace::Hsl => ["hue", "saturation", "lightness"][index],
+            ColorSpace::Rgb => ["red", "green", "blue"][index],
+        }
+    }
+
+    fn default_range(index: usize, space: ColorSpace) -> (f32, f32) {
+        match (space, index) {
+            (ColorSpace::Hsv | ColorSpace::Hsl, 0) => (0.0, 360.0),
+            _ => (0.0, 1.0),
+        }
+    }
+
+    fn component(&self, index: usize, space: ColorSpace) -> f32 {
+        match space {
+            ColorSpace::Hsv => {
+                let hsva = Hsva::from(*self);
+                [hsva.hue, hsva.saturation, hsva.value][index]
+            }
+            ColorSpace::Hsl => {
+                let hsla = Hsla::from(*self);
+                [hsla.hue, hsla.saturation, hsla.lightness][index]
+            }
+            ColorSpace::Rgb => {
+                let srgba = Srgba::from(*self);
+                [srgba.red, srgba.green, srgba.blue][index]
+            }
+        }
+    }
+
+    fn set_component(&mut self, index: usize, value: f32, space: ColorSpace) -> bool {
+        match space {
+            ColorSpace::Hsv => {
+                let mut hsva = Hsva::from(*self);
+                let Hsva { hue, saturation, value: v, .. } = &mut hsva;
+                if !set_if_changed([hue, saturation, v][index], value) {
+                    return false;
+                }
+                *self = Color::Hsva(hsva);
+            }
+            ColorSpace::Hsl => {
+                let mut hsla = Hsla::from(*self);
+                let Hsla { hue, saturation, lightness, .. } = &mut hsla;
+                if !set_if_changed([hue, saturation, lightness][index], value) {
+                    return false;
+                }
+                *self = Color::Hsla(hsla);
+            }
+            ColorSpace::Rgb => {
+                let mut srgba = Srgba::from(*self);
+                let Srgba { red, green, blue, .. } = &mut srgba;
+                if !set_if_changed([red, green, blue][index], value) {
+                    return false;
+                }
+                *self = Color::Srgba(srgba);
+            }
+        }
+        true
+    }
+
+    fn to_persist_value(&self, space: ColorSpace) -> Value {
+        let mut components: serde_json::Map<String, Value> = (0..Self::COUNT)
+            .map(|index| {
+                let name = Self::component_name(index, space).to_string();
+                (name, json!(self.component(index, space)))
+            })
+            .collect();
+        components.insert("alpha".to_string(), json!(self.alpha()));
+        Value::Object(components)
+    }
+
+    fn from_persist_value(value: &Value, space: ColorSpace) -> Result<Self, String> {
+        let number = |name: &str| {
+            value
+                .get(name)
+                .and_then(Value::as_f64)
+                .map(|number| number as f32)
+                .ok_or_else(|| format!("expected '{}' as a number", name))
+        };
+        let [a, b, c] = [0, 1, 2].map(|index| number(Self::component_name(index, space)));
+        let alpha = number("alpha")?;
+
+        Ok(match space {
+            ColorSpace::Hsv => Color::hsva(a?, b?, c?, alpha),
+            ColorSpace::Hsl => Color::hsla(a?, b?, c?, alpha),
+            ColorSpace::Rgb => Color::srgba(a?, b?, c?, alpha),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-3, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn color_knobs_keep_the_hue_of_grey_colors() {
+        let mut color = Color::srgb(1.0, 0.0, 0.0);
+
+        assert!(color.set_component(1, 0.0, ColorSpace::Hsv));
+        assert!(color.set_component(0, 120.0, ColorSpace::Hsv));
+        assert!(color.set_component(1, 1.0, ColorSpace::Hsv));
+        assert_close(color.component(0, ColorSpace::Hsv), 120.0);
+        assert_close(color.component(1, ColorSpace::Rgb), 1.0);
+        assert!(!color.set_component(1, 1.0, ColorSpace::Rgb));
+        assert_eq!(Color::component_index("lightness", ColorSpace::Hsl), Some(2));
+        assert_eq!(Color::component_index("lightness", ColorSpace::Hsv), None);
+    }
+
+    #[test]
+    fn rotations_follow_euler_angles_in_degrees() {
+        let mut rotation = Quat::IDENTITY;
+
+        rotation.set_component(0, 90.0, ColorSpace::default());
+        rotation.set_component(1, 30.0, ColorSpace::default());
+        assert_close(rotation.component(0, ColorSpace::default()), 90.0);
+        assert_close(rotation.component(1, ColorSpace::default()), 30.0);
+        assert_close(rotation.component(2, ColorSpace::default()), 0.0);
+    }
+
+    #[test]
+    fn every_rotation_component_round_trips() {
+        for index in 0..Quat::COUNT {
+            let (min, max) = Quat::default_range(index, ColorSpace::default());
+            for value in [min + 1.0, min / 2.0, 0.0, max / 2.0, max - 1.0] {
+                let mut angles = [30.0, 20.0, -40.0];
+                angles[index] = value;
+                let mut rotation =
+                    Quat::from_euler(EulerRot::YXZ, 30f32.to_radians(), 20f32.to_radians(), -40f32.to_radians());
+
+                rotation.set_component(index, value, ColorSpace::default());
+                for (component, angle) in angles.into_iter().enumerate() {
+                    assert_close(rotation.component(component, ColorSpace::default()), angle);
+                }
+            }
+        }
+
+        let mut rotation = Quat::IDENTITY;
+        rotation.set_component(1, 120.0, ColorSpace::default());
+        assert_close(rotation.component(1, ColorSpace::default()), 90.0);
+    }
+
+    #[test]
+    fn yaw_and_roll_are_undetermined_at_a_pitch_of_90() {
+        let space = ColorSpace::default();
+        let mut rotation =
+            Quat::from_euler(EulerRot::YXZ, 30f32.to_radians(), 20f32.to_radians(), -40f32.to_radians());
+        assert!((0..Quat::COUNT).all(|index| rotation.is_determined(index, space)));
+
+        for pitch in [90.0, -90.0] {
+            rotation.set_component(1, pitch, space);
+            assert_close(rotation.component(1, space), pitch);
+            assert!(rotation.is_determined(1, space));
+            assert!(!rotation.is_determined(0, space));
+            assert!(!rotation.is_determined(2, space));
+
+            // Whatever split is reported still describes the same rotation
+            let [yaw, pitch, roll] = [0, 1, 2].map(|index| rotation.component(index, space).to_radians());
+            assert_close(Quat::from_euler(EulerRot::YXZ, yaw, pitch, roll).angle_between(rotation), 0.0);
+        }
+    }
+
+    #[test]
+    fn components_persist_in_a_readable_form() {
+        let rotation = Quat::from_euler(EulerRot::YXZ, 45f32.to_radians(), 0.0, 0.0);
+
+        let space = ColorSpace::default();
+
+        assert_eq!(Vec2::new(0.5, 1.0).to_persist_value(space), json!([0.5, 1.0]));
+        assert!(Vec3::from_persist_value(&json!([1.0, 2.0]), space).is_err());
+        assert_close(rotation.to_persist_value(space)["yaw"].as_f64().unwrap() as f32, 45.0);
+        assert_close(
+            Quat::from_persist_value(&rotation.to_persist_value(space), space)
+                .unwrap()
+                .angle_between(rotation),
+            0.0,
+        );
+
+        let orange = Color::srgba(1.0, 0.5, 0.0, 0.25);
+        assert_eq!(
+            orange.to_persist_value(ColorSpace::Rgb),
+            json!({ "red": 1.0, "green": 0.5, "blue": 0.0, "alpha": 0.25 })
+        );
+        assert!(Color::from_persist_value(&json!({ "red": 1.0 }), ColorSpace::Rgb).is_err());
+
+        // Greys keep the hue they were given
+        let grey = Color::hsva(120.0, 0.0, 0.5, 1.0);
+        let restored =
+            Color::from_persist_value(&grey.to_persist_value(ColorSpace::Hsv), ColorSpace::Hsv);
+        assert_eq!(restored, Ok(grey));
+    }
+}
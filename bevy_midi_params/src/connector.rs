@@ -0,0 +1,32 @@
+use crate::MidiResult;
+use std::any::Any;
+
+/// A port opened by a `MidiConnector`, ready to be attached to the source or sink it came from
+pub struct OpenedPort {
+    /// Name of the port
+    pub name: String,
+    /// Backend connection, kept open for as long as the port is attached
+    pub connection: Box<dyn Any + Send>,
+}
+
+impl OpenedPort {
+    pub fn new(name: impl Into<String>, connection: impl Any + Send) -> Self {
+        Self {
+            name: name.into(),
+            connection: Box::new(connection),
+        }
+    }
+}
+
+/// Lists and opens the ports of a `MidiSource` or `MidiSink`
+///
+/// Device refreshes run on the connection thread against a connector, which has its own backend
+/// client. A backend that stalls while scanning therefore never holds up the source or sink, and
+/// ports that are already open keep working.
+pub trait MidiConnector: Send + 'static {
+    /// Names of the ports that can currently be connected to
+    fn available_ports(&mut self) -> MidiResult<Vec<String>>;
+
+    /// Open the named port, it is used once attached to the source or sink
+    fn connect(&mut self, port_name: &str) -> MidiResult<OpenedPort>;
+}
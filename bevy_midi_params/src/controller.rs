@@ -1,29 +1,174 @@
-use crate::{MidiError, MidiMapping, MidiPersistFile, MidiResult};
+use crate::{
+    ControlDecoder, ControlEvent, MidiConnector, MidiControl, MidiError, MidiMapping, MidiMessage,
+    MidiPersistFile, MidiResult, MidiSink, MidiSource, MidirSink, MidirSource, OpenedPort,
+    ReceivedMessage,
+};
 use bevy::prelude::*;
-use log::{debug, info};
-#[cfg(feature = "midi")]
-use midir::{Ignore, MidiInput, MidiInputConnection};
+use log::{debug, info, warn};
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// A device appearing or disappearing, reported by `MidiController::refresh_devices`
+#[cfg(feature = "midi")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceChange {
+    Connected(String),
+    Disconnected(String),
+}
+
+/// Connection status of the MIDI input, kept up to date by `MidiControlPlugin`
+#[cfg(feature = "midi")]
+#[derive(Resource, Debug, Clone, PartialEq, Default)]
+pub enum MidiConnectionState {
+    /// No device is open
+    #[default]
+    Disconnected,
+    /// A connection attempt is in progress
+    Connecting,
+    /// Names of the open input ports (at least one)
+    Connected(Vec<String>),
+    /// The last connection attempt failed and no device is open
+    Failed(MidiError),
+}
+
+#[cfg(feature = "midi")]
+impl MidiConnectionState {
+    /// Whether at least one device is open
+    pub fn is_connected(&self) -> bool {
+        matches!(self, MidiConnectionState::Connected(_))
+    }
+}
+
+/// Ports opened and closed by a device refresh, and its result
+#[cfg(feature = "midi")]
+struct RefreshOutcome {
+    /// Connectors to reuse for the next refresh
+    connectors: Option<Connectors>,
+    /// Input ports found by the scan
+    available: Vec<String>,
+    /// Input ports that should be open once the outcome is applied
+    connected: Vec<String>,
+    /// Output ports as (input device, output port) pairs that should be open
+    outputs: Vec<(String, String)>,
+    /// Newly opened input ports
+    opened_inputs: Vec<OpenedPort>,
+    /// Newly opened output ports
+    opened_outputs: Vec<OpenedPort>,
+    result: MidiResult<Vec<DeviceChange>>,
+}
+
+/// Device refresh started by `MidiController::start_refresh`
+#[cfg(feature = "midi")]
+enum PendingRefresh {
+    /// Running on the connection thread
+    Running {
+        handle: JoinHandle<RefreshOutcome>,
+        started: Instant,
+    },
+    /// Finished straight away (source does not block)
+    Done(RefreshOutcome),
+}
+
+/// Connectors for the input and output ports
+#[cfg(feature = "midi")]
+struct Connectors {
+    inputs: Box<dyn MidiConnector>,
+    outputs: Box<dyn MidiConnector>,
+}
+
+/// Everything a refresh needs, moved to the connection thread
+///
+/// The job only talks to the backend through its own connectors, the source and sink stay with
+/// the controller so open ports keep working while it runs.
+#[cfg(feature = "midi")]
+struct RefreshJob {
+    connectors: Connectors,
+    connected: Vec<String>,
+    outputs: Vec<(String, String)>,
+    preferred: Vec<String>,
+    feedback: bool,
+}
+
+#[cfg(feature = "midi")]
+impl RefreshJob {
+    fn run(mut self) -> RefreshOutcome {
+        let mut available = Vec::new();
+        let mut opened_inputs = Vec::new();
+        let result = refresh_ports(
+            &mut *self.connectors.inputs,
+            &mut self.connected,
+            &self.preferred,
+            &mut available,
+            &mut opened_inputs,
+        );
+
+        // Outputs are only kept for open inputs, and only while feedback is wanted
+        let inputs: &[String] = if self.feedback { &self.connected } else { &[] };
+        let mut opened_outputs = Vec::new();
+        refresh_outputs(
+            &mut *self.connectors.outputs,
+            inputs,
+            &mut self.outputs,
+            &mut opened_outputs,
+        );
+
+        RefreshOutcome {
+            connectors: Some(self.connectors),
+            available,
+            connected: self.connected,
+            outputs: self.outputs,
+            opened_inputs,
+            opened_outputs,
+            result,
+        }
+    }
+}
 
 /// Resource that manages MIDI controller input and state
 #[cfg(feature = "midi")]
 #[derive(Resource)]
 pub struct MidiController {
-    /// Current MIDI CC values (normalized 0.0-1.0)
-    pub values: HashMap<u8, f32>,
+    /// Last received control values (normalized 0.0-1.0) keyed by (device, channel, control),
+    /// only contains controls that have been moved
+    pub values: HashMap<(usize, u8, MidiControl), f32>,
+    /// Messages received since the previous frame, in arrival order
+    messages: Vec<ReceivedMessage>,
+    /// Control events received since the previous frame, in arrival order
+    control_events: Vec<ControlEvent>,
+    /// Names of every device that has been connected, indexed by `ControlEvent::device`
+    devices: Vec<String>,
+    /// Names of the ports that are currently open
+    connected: Vec<String>,
+    /// Open output ports as (input device, output port) pairs
+    outputs: Vec<(String, String)>,
+    /// Bumped whenever an output is opened, so feedback can resend everything to it
+    output_generation: u64,
+    /// Whether field values are sent back to devices
+    feedback: bool,
+    /// Set when a device scan has been requested (e.g. from the UI)
+    scan_requested: bool,
+    /// Refresh in progress, if any
+    pending_refresh: Mutex<Option<PendingRefresh>>,
+    /// Connectors of the last refresh, reused by the next one unless it timed out
+    connectors: Mutex<Option<Connectors>>,
+    /// Input ports found by the last refresh
+    available: Vec<String>,
+    /// One decoder per device (pairs 14-bit CCs, assembles NRPNs)
+    decoders: Vec<ControlDecoder>,
     /// All registered MIDI mappings
-    mappings: HashMap<u8, MidiMapping>,
+    mappings: Vec<MidiMapping>,
     /// Path to persistence file
     persist_file_path: String,
     /// List of registered type names
     registered_types: Vec<&'static str>,
-    /// MIDI connection (kept alive)
-    _connection: Option<Arc<Mutex<Option<MidiInputConnection<()>>>>>,
-    /// A shared pointer to values which are updated by the connection
-    _changed_values: Option<Arc<Mutex<HashMap<u8, f32>>>>,
-    /// Preferred MIDI controller name (partial match)
-    preferred_controller: Option<String>,
+    /// Transport delivering MIDI messages (midir unless replaced with `with_source`)
+    source: Mutex<Box<dyn MidiSource>>,
+    /// Transport sending feedback to devices (midir unless replaced with `with_sink`)
+    sink: Mutex<Box<dyn MidiSink>>,
+    /// Preferred MIDI controller names (partial match), every matching port is opened
+    preferred_controllers: Vec<String>,
 }
 
 #[cfg(feature = "midi")]
@@ -31,18 +176,93 @@ impl MidiController {
     pub fn new(persist_path: Option<String>, preferred_controller: Option<String>) -> Self {
         Self {
             values: HashMap::new(),
-            mappings: HashMap::new(),
+            messages: Vec::new(),
+            control_events: Vec::new(),
+            devices: Vec::new(),
+            connected: Vec::new(),
+            outputs: Vec::new(),
+            output_generation: 0,
+            feedback: true,
+            scan_requested: false,
+            pending_refresh: Mutex::new(None),
+            connectors: Mutex::new(None),
+            available: Vec::new(),
+            decoders: Vec::new(),
+            mappings: Vec::new(),
             persist_file_path: persist_path.unwrap_or_else(|| "midi_settings.ron".to_string()),
             registered_types: Vec::new(),
-            _connection: None,
-            _changed_values: None,
-            preferred_controller,
+            source: Mutex::new(Box::new(MidirSource::default())),
+            sink: Mutex::new(Box::new(MidirSink::default())),
+            preferred_controllers: preferred_controller.into_iter().collect(),
         }
     }
 
-    /// Get current value for a CC (normalized 0.0-1.0)
-    pub fn get_value(&self, cc: u8) -> f32 {
-        self.values.get(&cc).copied().unwrap_or(0.0)
+    /// Replace the MIDI transport, e.g. with a `ChannelSource` for tests
+    pub fn with_source(mut self, source: impl MidiSource) -> Self {
+        self.set_source(Box::new(source));
+        self
+    }
+
+    pub(crate) fn set_source(&mut self, source: Box<dyn MidiSource>) {
+        self.source = Mutex::new(source);
+        *exclusive(&mut self.connectors) = None;
+    }
+
+    /// Replace the MIDI output transport, e.g. with a `ChannelSink` for tests
+    pub fn with_sink(mut self, sink: impl MidiSink) -> Self {
+        self.set_sink(Box::new(sink));
+        self
+    }
+
+    pub(crate) fn set_sink(&mut self, sink: Box<dyn MidiSink>) {
+        self.sink = Mutex::new(sink);
+        *exclusive(&mut self.connectors) = None;
+    }
+
+    /// Enable or disable sending field values back to devices
+    ///
+    /// Outputs are opened and closed on the next device refresh.
+    pub fn set_feedback(&mut self, enabled: bool) {
+        self.feedback = enabled;
+    }
+
+    /// Whether field values are sent back to devices
+    pub fn feedback_enabled(&self) -> bool {
+        self.feedback
+    }
+
+    /// Also connect to ports matching this name (partial match)
+    pub fn with_controller(mut self, controller_name: impl Into<String>) -> Self {
+        self.preferred_controllers.push(controller_name.into());
+        self
+    }
+
+    /// Get current value for a control on a device and channel (normalized 0.0-1.0)
+    pub fn get_value(&self, device: usize, channel: u8, control: MidiControl) -> f32 {
+        self.values
+            .get(&(device, channel, control))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// All messages received since the previous frame
+    pub fn messages(&self) -> &[ReceivedMessage] {
+        &self.messages
+    }
+
+    /// Control events (CCs and notes) received since the previous frame
+    pub fn control_events(&self) -> &[ControlEvent] {
+        &self.control_events
+    }
+
+    /// Names of every device that has ever been connected, in connection order
+    pub fn devices(&self) -> &[String] {
+        &self.devices
+    }
+
+    /// Name of the device with the given index
+    pub fn device_name(&self, device: usize) -> Option<&str> {
+        self.devices.get(device).map(String::as_str)
     }
 
     /// Get the number of registered types
@@ -50,19 +270,29 @@ impl MidiController {
         self.registered_types.len()
     }
 
-    /// Get scaled value for a CC using the registered mapping
-    pub fn get_scaled_value(&self, cc: u8) -> Option<f32> {
-        let mapping = self.mappings.get(&cc)?;
-        let normalized = self.get_value(cc);
+    /// Get scaled value for a control on a device and channel using the registered mapping
+    pub fn get_scaled_value(&self, device: usize, channel: u8, control: MidiControl) -> Option<f32> {
+        let mapping = self.find_mapping(device, channel, control)?;
+        let normalized = self.get_value(device, channel, control);
         Some(mapping.scale_value(normalized))
     }
 
+    /// Find the mapping for a control on a device and channel
+    ///
+    /// Mappings restricted to a device or channel win over ones that accept any.
+    pub fn find_mapping(&self, device: usize, channel: u8, control: MidiControl) -> Option<&MidiMapping> {
+        let device_name = self.device_name(device)?;
+        self.mappings
+            .iter()
+            .filter(|mapping| mapping.responds_to(device_name, channel, control))
+            .max_by_key(|mapping| (mapping.device.is_some(), mapping.channel.is_some()))
+    }
+
     /// Register a MIDI mapping
     pub fn register_mapping(&mut self, mapping: MidiMapping) {
         // Only register mappings that have MIDI control enabled
-        if let Some(cc) = mapping.cc {
-            self.values.insert(cc, 0.0);
-            self.mappings.insert(cc, mapping);
+        if mapping.has_midi_control() {
+            self.mappings.push(mapping);
         }
     }
 
@@ -75,7 +305,7 @@ impl MidiController {
     }
 
     /// Get all registered mappings
-    pub fn get_mappings(&self) -> &HashMap<u8, MidiMapping> {
+    pub fn get_mappings(&self) -> &[MidiMapping] {
         &self.mappings
     }
 
@@ -89,84 +319,425 @@ impl MidiController {
         data.save_to_file(&self.persist_file_path)
     }
 
-    /// Connect to MIDI input device
+    /// Connect to MIDI input devices
+    ///
+    /// Opens every port matching a preferred controller name, every port when none are
+    /// preferred, or the first port when none match.
     pub fn connect_midi(&mut self) -> MidiResult<()> {
-        let mut midi_in = MidiInput::new("bevy_midi_params").map_err(|e| {
-            MidiError::ConnectionFailed(format!("Failed to create MIDI input: {}", e))
-        })?;
+        self.refresh_devices()?;
 
-        midi_in.ignore(Ignore::None);
-
-        let in_ports = midi_in.ports();
-        if in_ports.is_empty() {
+        if self.connected.is_empty() {
             return Err(MidiError::NoInputPorts);
         }
+        Ok(())
+    }
 
-        // Use preferred controller if specified, otherwise first available
-        let in_port = if let Some(ref preferred) = self.preferred_controller {
-            in_ports
-                .iter()
-                .find(|port| {
-                    midi_in
-                        .port_name(port)
-                        .unwrap_or_default()
-                        .to_lowercase()
-                        .contains(&preferred.to_lowercase())
-                })
-                .or_else(|| in_ports.first())
-        } else {
-            in_ports.first()
-        }
-        .unwrap();
-
-        let port_name = midi_in.port_name(in_port).unwrap_or("Unknown".to_string());
-        info!("Connecting to MIDI port: {}", port_name);
+    /// Close ports that have disappeared and open preferred ports that have (re)appeared
+    ///
+    /// Without preferred controllers every port is preferred. Falls back to the first available
+    /// port while nothing is connected and no preferred port is present. Returns the devices that were connected or disconnected.
+    ///
+    /// This blocks on the MIDI backend, `MidiControlPlugin` uses `start_refresh` instead.
+    pub fn refresh_devices(&mut self) -> MidiResult<Vec<DeviceChange>> {
+        if self.is_refreshing() {
+            return Err(MidiError::ConnectionFailed(
+                "A device refresh is already running".to_string(),
+            ));
+        }
 
-        // Shared values for the callback
-        let raw_values = Arc::new(Mutex::new(HashMap::<u8, f32>::new()));
-        self._changed_values = Some(raw_values.clone());
-        let values_clone = raw_values.clone();
-
-        let connection = midi_in
-            .connect(
-                in_port,
-                "bevy-midi-params",
-                move |_stamp, message, _| {
-                    if message.len() >= 3 && message[0] == 0xB0 {
-                        // Control Change
-                        let cc = message[1];
-                        let value = message[2] as f32 / 127.0; // Normalize to 0.0-1.0
-
-                        if let Ok(mut values) = values_clone.lock() {
-                            values.insert(cc, value);
-                        }
-
-                        info!("MIDI CC {}: {:.3}", cc, value);
-                    }
-                },
-                (),
-            )
-            .map_err(|e| MidiError::ConnectionFailed(format!("Connection failed: {}", e)))?;
-
-        self._connection = Some(Arc::new(Mutex::new(Some(connection))));
-        Ok(())
+        let outcome = self.refresh_job().run();
+        self.apply_refresh(outcome)
+    }
+
+    fn refresh_job(&mut self) -> RefreshJob {
+        let connectors = exclusive(&mut self.connectors).take().unwrap_or_else(|| Connectors {
+            inputs: lock_source(&self.source).connector(),
+            outputs: lock_sink(&self.sink).connector(),
+        });
+
+        RefreshJob {
+            connectors,
+            connected: self.connected.clone(),
+            outputs: self.outputs.clone(),
+            preferred: self.preferred_controllers.clone(),
+            feedback: self.feedback,
+        }
     }
 
-    /// Update values from MIDI (called by system)
+    /// Close the ports a finished refresh dropped, attach the ones it opened and return its result
+    fn apply_refresh(&mut self, outcome: RefreshOutcome) -> MidiResult<Vec<DeviceChange>> {
+        let source = exclusive(&mut self.source);
+        for port in &self.connected {
+            if !outcome.connected.contains(port) {
+                source.disconnect(port);
+            }
+        }
+        for port in outcome.opened_inputs {
+            source.attach(port);
+        }
+
+        let sink = exclusive(&mut self.sink);
+        for output in &self.outputs {
+            if !outcome.outputs.contains(output) {
+                sink.disconnect(&output.1);
+            }
+        }
+        for port in outcome.opened_outputs {
+            sink.attach(port);
+        }
+
+        if outcome
+            .outputs
+            .iter()
+            .any(|output| !self.outputs.contains(output))
+        {
+            self.output_generation += 1;
+        }
+
+        *exclusive(&mut self.connectors) = outcome.connectors;
+        self.available = outcome.available;
+        self.connected = outcome.connected;
+        self.outputs = outcome.outputs;
+        outcome.result
+    }
+
+    /// Start refreshing devices without blocking, collect the result with `poll_refresh`
+    ///
+    /// Sources that may block are refreshed on a background thread. Does nothing if a
+    /// refresh is already running.
+    pub fn start_refresh(&mut self) {
+        if self.is_refreshing() {
+            return;
+        }
+
+        let job = self.refresh_job();
+
+        if !lock_source(&self.source).connects_in_background() {
+            *exclusive(&mut self.pending_refresh) = Some(PendingRefresh::Done(job.run()));
+            return;
+        }
+
+        let spawned = std::thread::Builder::new()
+            .name("bevy_midi_params connect".to_string())
+            .spawn(move || job.run());
+
+        let pending = match spawned {
+            Ok(handle) => PendingRefresh::Running {
+                handle,
+                started: Instant::now(),
+            },
+            Err(e) => PendingRefresh::Done(self.failed_refresh(MidiError::ConnectionFailed(
+                format!("Failed to start connection thread: {}", e),
+            ))),
+        };
+        *exclusive(&mut self.pending_refresh) = Some(pending);
+    }
+
+    /// Outcome of a refresh that did not get to change anything
+    fn failed_refresh(&self, error: MidiError) -> RefreshOutcome {
+        RefreshOutcome {
+            connectors: None,
+            available: self.available.clone(),
+            connected: self.connected.clone(),
+            outputs: self.outputs.clone(),
+            opened_inputs: Vec::new(),
+            opened_outputs: Vec::new(),
+            result: Err(error),
+        }
+    }
+
+    /// Collect the result of a refresh started with `start_refresh`, None while it is running
+    ///
+    /// A refresh running longer than `timeout` is abandoned and reported as
+    /// `MidiError::ConnectionTimeout`, so the next refresh can start with fresh connectors.
+    pub fn poll_refresh(&mut self, timeout: Duration) -> Option<MidiResult<Vec<DeviceChange>>> {
+        let outcome = match exclusive(&mut self.pending_refresh).take()? {
+            PendingRefresh::Done(outcome) => outcome,
+            PendingRefresh::Running { handle, started } => {
+                if handle.is_finished() {
+                    handle.join().unwrap_or_else(|_| {
+                        self.failed_refresh(MidiError::ConnectionFailed(
+                            "MIDI backend panicked while connecting".to_string(),
+                        ))
+                    })
+                } else if started.elapsed() >= timeout {
+                    // The thread is left to finish on its own, whatever it opens is dropped
+                    self.failed_refresh(MidiError::ConnectionTimeout(timeout))
+                } else {
+                    *exclusive(&mut self.pending_refresh) =
+                        Some(PendingRefresh::Running { handle, started });
+                    return None;
+                }
+            }
+        };
+
+        Some(self.apply_refresh(outcome))
+    }
+
+    /// Whether a refresh started with `start_refresh` has not been collected yet
+    pub fn is_refreshing(&self) -> bool {
+        self.pending_refresh
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
+    }
+
+    /// Names of the ports that are currently open
+    pub fn connected_devices(&self) -> &[String] {
+        &self.connected
+    }
+
+    /// Names of the connected devices that have an output port, so they receive feedback
+    pub fn output_devices(&self) -> impl Iterator<Item = &str> {
+        self.outputs.iter().map(|(device, _)| device.as_str())
+    }
+
+    /// Changes whenever an output is opened (called by system)
+    pub(crate) fn output_generation(&self) -> u64 {
+        self.output_generation
+    }
+
+    /// Send a message to the output port of a connected device
+    ///
+    /// Fails if the device has no output.
+    pub fn send(&self, device: &str, message: &MidiMessage) -> MidiResult<()> {
+        let (_, port) = self
+            .outputs
+            .iter()
+            .find(|(name, _)| name == device)
+            .ok_or_else(|| {
+                MidiError::ConnectionFailed(format!("MIDI device '{}' has no open output", device))
+            })?;
+
+        lock_sink(&self.sink).send(port, message)
+    }
+
+    /// Send feedback messages to every output whose device the mapping accepts (called by system)
+    pub(crate) fn send_feedback(&self, mapping: &MidiMapping, messages: &[MidiMessage]) {
+        let mut sink = lock_sink(&self.sink);
+
+        for (_, port) in self
+            .outputs
+            .iter()
+            .filter(|(device, _)| mapping.matches_device(device))
+        {
+            for message in messages {
+                if let Err(e) = sink.send(port, message) {
+                    debug!("Failed to send MIDI feedback to {}: {}", port, e);
+                }
+            }
+        }
+    }
+
+    /// Names of the input ports found by the last device refresh
+    pub fn available_ports(&self) -> &[String] {
+        &self.available
+    }
+
+    /// Connect to a port on the next device scan (e.g. one picked from `available_ports`)
+    ///
+    /// The port is added to the preferred controllers, so it is also reconnected after being
+    /// unplugged.
+    pub fn connect_device(&mut self, port_name: impl Into<String>) {
+        let port_name = port_name.into();
+        if !self.preferred_controllers.contains(&port_name) {
+            self.preferred_controllers.push(port_name);
+        }
+        self.request_scan();
+    }
+
+    /// Ask the device scanner to refresh on the next frame, regardless of its interval
+    pub fn request_scan(&mut self) {
+        self.scan_requested = true;
+    }
+
+    /// Take a pending scan request (called by system)
+    pub(crate) fn take_scan_request(&mut self) -> bool {
+        std::mem::take(&mut self.scan_requested)
+    }
+
+    /// Index of a device, assigning a new one the first time it is seen
+    fn device_index(&mut self, port: &str) -> usize {
+        if let Some(index) = self.devices.iter().position(|device| device == port) {
+            return index;
+        }
+        let index = self.devices.len();
+        self.devices.push(port.to_string());
+        self.decoders.push(ControlDecoder::for_device(index));
+        index
+    }
+
+    /// Move messages received by the source into this frame's queues (called by system)
     pub(crate) fn update_values(&mut self) {
-        let Some(changed_values) = &self._changed_values else {
+        self.messages.clear();
+        self.control_events.clear();
+
+        exclusive(&mut self.source).poll(&mut self.messages);
+
+        let messages = std::mem::take(&mut self.messages);
+        for received in &messages {
+            let device = self.device_index(&received.port);
+            self.decoders[device].decode(&received.message, &mut self.control_events);
+        }
+        self.messages = messages;
+
+        for event in &self.control_events {
+            self.values
+                .insert((event.device, event.channel, event.control), event.value);
+        }
+    }
+}
+
+/// Lock the source, recovering it if a system panicked while holding it
+#[cfg(feature = "midi")]
+fn lock_source(source: &Mutex<Box<dyn MidiSource>>) -> MutexGuard<'_, Box<dyn MidiSource>> {
+    source.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Access a mutex owned through `&mut`, recovering it if a system panicked while holding it
+///
+/// The controller keeps its transports and refresh state in mutexes only so it can be shared
+/// as a resource, so this never waits.
+#[cfg(feature = "midi")]
+fn exclusive<T>(mutex: &mut Mutex<T>) -> &mut T {
+    mutex.get_mut().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Lock the sink, recovering it if a system panicked while holding it
+#[cfg(feature = "midi")]
+fn lock_sink(sink: &Mutex<Box<dyn MidiSink>>) -> MutexGuard<'_, Box<dyn MidiSink>> {
+    sink.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Output port belonging to an input port
+///
+/// Most backends give both the same name, others decorate it (e.g. "MIDIOUT2 (MIDImix)").
+#[cfg(feature = "midi")]
+fn output_port_for<'a>(input: &str, output_ports: &'a [String]) -> Option<&'a String> {
+    let input_lower = input.to_lowercase();
+    output_ports
+        .iter()
+        .find(|port| *port == input)
+        .or_else(|| output_ports.iter().find(|port| port.to_lowercase().contains(&input_lower)))
+}
+
+/// Drop outputs whose input has gone and open an output for every input that has one
+///
+/// Devices without an output simply get no feedback, so failures are only logged.
+#[cfg(feature = "midi")]
+fn refresh_outputs(
+    connector: &mut dyn MidiConnector,
+    inputs: &[String],
+    outputs: &mut Vec<(String, String)>,
+    opened: &mut Vec<OpenedPort>,
+) {
+    outputs.retain(|(device, _)| inputs.contains(device));
+
+    // Only ask the backend for ports when there is something to open
+    if inputs
+        .iter()
+        .all(|input| outputs.iter().any(|(device, _)| device == input))
+    {
+        return;
+    }
+
+    let output_ports = match connector.available_ports() {
+        Ok(ports) => ports,
+        Err(e) => {
+            debug!("Failed to list MIDI output ports: {}", e);
             return;
+        }
+    };
+
+    for input in inputs {
+        if outputs.iter().any(|(device, _)| device == input) {
+            continue;
+        }
+        let Some(port) = output_port_for(input, &output_ports) else {
+            continue;
         };
 
-        if let Ok(mut changed_values_lock) = changed_values.lock() {
-            // Move all values out instead of cloning
-            for (cc, value) in changed_values_lock.drain() {
-                self.values.insert(cc, value);
+        match connector.connect(port) {
+            Ok(opened_port) => {
+                info!("Opened MIDI output for {}: {}", input, port);
+                outputs.push((input.clone(), port.clone()));
+                opened.push(opened_port);
             }
+            Err(e) => debug!("Failed to open MIDI output {}: {}", port, e),
         }
     }
 }
 
+/// Drop `connected` ports that have disappeared and open preferred ports that have (re)appeared
+///
+/// Every port counts as preferred when `preferred_controllers` is empty. The ports found are
+/// stored in `available` and the ones opened in `opened`.
+#[cfg(feature = "midi")]
+fn refresh_ports(
+    connector: &mut dyn MidiConnector,
+    connected: &mut Vec<String>,
+    preferred_controllers: &[String],
+    available: &mut Vec<String>,
+    opened: &mut Vec<OpenedPort>,
+) -> MidiResult<Vec<DeviceChange>> {
+    let ports = connector.available_ports()?;
+    let mut changes = Vec::new();
+
+    // Drop ports whose device has been unplugged
+    connected.retain(|port| {
+        if ports.contains(port) {
+            return true;
+        }
+        info!("MIDI device disconnected: {}", port);
+        changes.push(DeviceChange::Disconnected(port.clone()));
+        false
+    });
+
+    // Without preferred names every device is wanted
+    let mut selected: Vec<&String> = ports
+        .iter()
+        .filter(|port| {
+            let port = port.to_lowercase();
+            preferred_controllers.is_empty()
+                || preferred_controllers
+                    .iter()
+                    .any(|preferred| port.contains(&preferred.to_lowercase()))
+        })
+        .collect();
+
+    if selected.is_empty() && connected.is_empty() {
+        selected.extend(ports.first());
+    }
+
+    let mut last_error = None;
+    for port_name in selected {
+        if connected.contains(port_name) {
+            continue;
+        }
+
+        info!("Connecting to MIDI port: {}", port_name);
+        match connector.connect(port_name) {
+            Ok(port) => {
+                connected.push(port_name.clone());
+                changes.push(DeviceChange::Connected(port_name.clone()));
+                opened.push(port);
+            }
+            Err(e) => {
+                warn!("Failed to connect to MIDI port {}: {}", port_name, e);
+                last_error = Some(e);
+            }
+        }
+    }
+
+    *available = ports;
+
+    // Only fail when nothing could be opened, partial success is still useful
+    match last_error {
+        Some(e) if connected.is_empty() => Err(e),
+        _ => Ok(changes),
+    }
+}
+
 #[cfg(feature = "midi")]
 impl Default for MidiController {
     fn default() -> Self {
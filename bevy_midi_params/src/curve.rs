@@ -0,0 +1,159 @@
+/// Shape applied between a control's position and a range field
+///
+/// Linear ranges make parameters such as light intensity or frequencies hard to dial in at
+/// the low end; a curve spends more of the control's travel where fine adjustment matters.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum ResponseCurve {
+    /// Equal change per step
+    #[default]
+    Linear,
+    /// Equal ratio per step (`min * (max / min)^x`) for positive ranges such as frequencies,
+    /// a 60 dB taper for ranges that start at or below zero
+    Logarithmic,
+    /// `x^power`, powers above 1.0 give finer control at the low end
+    Exponential(f32),
+    /// Smoothstep, fine control at both ends and fast through the middle
+    SCurve,
+    /// User-supplied monotonic function from control position to range position (both 0.0-1.0)
+    Function(fn(f32) -> f32),
+    /// Range positions at evenly spaced control positions, linearly interpolated
+    Table(&'static [f32]),
+}
+
+/// Gain of the logarithmic taper used for ranges that include zero (60 dB)
+const TAPER_BASE: f32 = 1000.0;
+
+impl ResponseCurve {
+    /// Map a control position (0.0-1.0) to a position within a range
+    pub fn apply(&self, x: f32) -> f32 {
+        let x = x.clamp(0.0, 1.0);
+        match *self {
+            ResponseCurve::Linear => x,
+            ResponseCurve::Logarithmic => (TAPER_BASE.powf(x) - 1.0) / (TAPER_BASE - 1.0),
+            ResponseCurve::Exponential(power) => x.powf(power),
+            ResponseCurve::SCurve => x * x * (3.0 - 2.0 * x),
+            ResponseCurve::Function(function) => function(x).clamp(0.0, 1.0),
+            ResponseCurve::Table(table) => interpolate(table, x),
+        }
+    }
+
+    /// Control position that produces a range position, the inverse of `apply`
+    pub fn invert(&self, y: f32) -> f32 {
+        let y = y.clamp(0.0, 1.0);
+        match *self {
+            ResponseCurve::Linear => y,
+            ResponseCurve::Logarithmic => (1.0 + y * (TAPER_BASE - 1.0)).ln() / TAPER_BASE.ln(),
+            ResponseCurve::Exponential(power) if power > 0.0 => y.powf(power.recip()),
+            _ => self.bisect(y),
+        }
+    }
+
+    /// Map a control position into `min..max`
+    pub fn scale(&self, x: f32, min: f32, max: f32) -> f32 {
+        match self {
+            ResponseCurve::Logarithmic if min > 0.0 && max > 0.0 => min * (max / min).powf(x.clamp(0.0, 1.0)),
+            curve => min + curve.apply(x) * (max - min),
+        }
+    }
+
+    /// Control position that produces `value` within `min..max`, the inverse of `scale`
+    pub fn normalize(&self, value: f32, min: f32, max: f32) -> f32 {
+        if max == min {
+            return 0.0;
+        }
+        match self {
+            ResponseCurve::Logarithmic if min > 0.0 && max > 0.0 => {
+                ((value.max(min) / min).ln() / (max / min).ln()).clamp(0.0, 1.0)
+            }
+            curve => curve.invert((value - min) / (max - min)),
+        }
+    }
+
+    /// Numeric inverse for curves without a closed form, assumes `apply` is non-decreasing
+    fn bisect(&self, y: f32) -> f32 {
+        let (mut low, mut high) = (0.0_f32, 1.0_f32);
+        for _ in 0..32 {
+            let mid = (low + high) * 0.5;
+            if self.apply(mid) < y {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        (low + high) * 0.5
+    }
+}
+
+impl PartialEq for ResponseCurve {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ResponseCurve::Linear, ResponseCurve::Linear)
+            | (ResponseCurve::Logarithmic, ResponseCurve::Logarithmic)
+            | (ResponseCurve::SCurve, ResponseCurve::SCurve) => true,
+            (ResponseCurve::Exponential(a), ResponseCurve::Exponential(b)) => a == b,
+            (ResponseCurve::Function(a), ResponseCurve::Function(b)) => std::ptr::fn_addr_eq(*a, *b),
+            (ResponseCurve::Table(a), ResponseCurve::Table(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Linearly interpolate evenly spaced points at `x` (0.0-1.0)
+fn interpolate(table: &[f32], x: f32) -> f32 {
+    match table {
+        [] => x,
+        [only] => *only,
+        _ => {
+            let position = x * (table.len() - 1) as f32;
+            let index = (position as usize).min(table.len() - 2);
+            let fraction = position - index as f32;
+            table[index] + (table[index + 1] - table[index]) * fraction
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-3, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn curves_invert_back_to_the_control_position() {
+        let curves = [
+            ResponseCurve::Linear,
+            ResponseCurve::Logarithmic,
+            ResponseCurve::Exponential(2.5),
+            ResponseCurve::SCurve,
+            ResponseCurve::Function(|x| x.sqrt()),
+            ResponseCurve::Table(&[0.0, 0.1, 0.3, 1.0]),
+        ];
+
+        for curve in curves {
+            for x in [0.0, 0.2, 0.5, 0.9, 1.0] {
+                assert_close(curve.invert(curve.apply(x)), x);
+                assert_close(curve.normalize(curve.scale(x, 0.0, 5000.0), 0.0, 5000.0), x);
+            }
+        }
+    }
+
+    #[test]
+    fn logarithmic_positive_ranges_are_geometric() {
+        let curve = ResponseCurve::Logarithmic;
+
+        assert_close(curve.scale(0.0, 20.0, 20000.0), 20.0);
+        assert_close(curve.scale(0.5, 20.0, 20000.0), 632.456);
+        assert_close(curve.normalize(2000.0, 20.0, 20000.0), 2.0 / 3.0);
+    }
+
+    #[test]
+    fn tables_interpolate_between_points() {
+        let curve = ResponseCurve::Table(&[0.0, 0.2, 1.0]);
+
+        assert_close(curve.apply(0.25), 0.1);
+        assert_close(curve.apply(0.75), 0.6);
+        assert_close(curve.apply(1.0), 1.0);
+    }
+}
@@ -0,0 +1,489 @@
+use crate::message::split_14bit;
+use crate::{ControlEvent, MidiControl, MidiMessage};
+
+/// Largest 14-bit value, used to normalize high resolution controls
+const MAX_14BIT: f32 = 16383.0;
+
+// Controller numbers used by NRPN/RPN sequences
+const DATA_ENTRY_MSB: u8 = 6;
+const DATA_ENTRY_LSB: u8 = 38;
+const DATA_INCREMENT: u8 = 96;
+const DATA_DECREMENT: u8 = 97;
+const NRPN_LSB: u8 = 98;
+const NRPN_MSB: u8 = 99;
+const RPN_LSB: u8 = 100;
+const RPN_MSB: u8 = 101;
+
+/// Which kind of parameter number is currently selected on a channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParamKind {
+    Nrpn,
+    Rpn,
+}
+
+/// NRPN/RPN selection and data entry state for one channel
+#[derive(Debug, Clone, Copy, Default)]
+struct ParamState {
+    nrpn: (u8, u8),
+    rpn: (u8, u8),
+    active: Option<ParamKind>,
+    /// Last 14-bit value sent to the active parameter, back to zero when another one is selected
+    value: u16,
+}
+
+impl ParamState {
+    fn active_control(&self) -> Option<MidiControl> {
+        match self.active? {
+            ParamKind::Nrpn => Some(MidiControl::Nrpn(crate::combine_14bit(self.nrpn.0, self.nrpn.1))),
+            ParamKind::Rpn => Some(MidiControl::Rpn(crate::combine_14bit(self.rpn.0, self.rpn.1))),
+        }
+    }
+}
+
+/// Turns decoded MIDI messages into control events
+///
+/// Keeps the per-channel state needed to pair 14-bit CCs (MSB on CC n, LSB on CC n+32)
+/// and to assemble NRPN/RPN sequences (CC 99/98 or 101/100, then data entry on CC 6/38).
+#[derive(Debug, Clone)]
+pub struct ControlDecoder {
+    /// Device index stamped on every event
+    device: usize,
+    /// Last MSB received for CCs 0-31 on each channel
+    hires_msb: [[u8; 32]; 16],
+    /// Parameter number state on each channel
+    params: [ParamState; 16],
+}
+
+impl ControlDecoder {
+    pub fn new() -> Self {
+        Self::for_device(0)
+    }
+
+    /// Create a decoder whose events are tagged with the given device index
+    pub fn for_device(device: usize) -> Self {
+        Self {
+            device,
+            hires_msb: [[0; 32]; 16],
+            params: [ParamState::default(); 16],
+        }
+    }
+
+    /// Decode a message, pushing any resulting control events
+    pub fn decode(&mut self, message: &MidiMessage, events: &mut Vec<ControlEvent>) {
+        // Normalize to 0.0-1.0
+        match *message {
+            MidiMessage::ControlChange {
+                channel,
+                control,
+                value,
+            } => {
+                // Parameter number sequences are not reported as plain CCs
+                if self.decode_parameter(channel, control, value, events) {
+                    return;
+                }
+
+                events.push(ControlEvent {
+                    device: self.device,
+                    channel,
+                    control: MidiControl::Cc(control),
+                    value: value as f32 / 127.0,
+                });
+                self.decode_hires(channel, control, value, events);
+            }
+            MidiMessage::NoteOn {
+                channel,
+                note,
+                velocity,
+            } => events.push(ControlEvent {
+                device: self.device,
+                channel,
+                control: MidiControl::Note(note),
+                value: velocity as f32 / 127.0,
+            }),
+            MidiMessage::NoteOff { channel, note, .. } => events.push(ControlEvent {
+                device: self.device,
+                channel,
+                control: MidiControl::Note(note),
+                value: 0.0,
+            }),
+            MidiMessage::PitchBend { channel, value } => events.push(ControlEvent {
+                device: self.device,
+                channel,
+                control: MidiControl::PitchBend,
+                value: normalize_pitch_bend(value),
+            }),
+            MidiMessage::ChannelAftertouch { channel, pressure } => events.push(ControlEvent {
+                device: self.device,
+                channel,
+                control: MidiControl::ChannelAftertouch,
+                value: pressure as f32 / 127.0,
+            }),
+            MidiMessage::PolyAftertouch {
+                channel,
+                note,
+                pressure,
+            } => events.push(ControlEvent {
+                device: self.device,
+                channel,
+                control: MidiControl::PolyAftertouch(note),
+                value: pressure as f32 / 127.0,
+            }),
+            _ => {}
+        }
+    }
+
+    /// Pair CC n (MSB) with CC n+32 (LSB) into a 14-bit value
+    ///
+    /// A new MSB resets the LSB to zero, as the MIDI spec requires, so controllers that
+    /// only send the MSB still drive high resolution mappings.
+    fn decode_hires(&mut self, channel: u8, control: u8, value: u8, events: &mut Vec<ControlEvent>) {
+        let channel_msb = &mut self.hires_msb[channel as usize & 0x0F];
+
+        let (msb_cc, combined) = match control {
+            0..=31 => {
+                channel_msb[control as usize] = value;
+                (control, crate::combine_14bit(value, 0))
+            }
+            32..=63 => {
+                let msb_cc = control - 32;
+                (msb_cc, crate::combine_14bit(channel_msb[msb_cc as usize], value))
+            }
+            _ => return,
+        };
+
+        events.push(ControlEvent {
+            device: self.device,
+            channel,
+            control: MidiControl::HiResCc(msb_cc),
+            value: combined as f32 / MAX_14BIT,
+        });
+    }
+
+    /// Track NRPN/RPN selection and data entry, returns true if the CC was consumed
+    ///
+    /// Selection CCs (98-101) are always consumed. Data entry CCs (6, 38, 96, 97) are only
+    /// consumed while a parameter is selected, otherwise they are ordinary CCs.
+    fn decode_parameter(
+        &mut self,
+        channel: u8,
+        control: u8,
+        value: u8,
+        events: &mut Vec<ControlEvent>,
+    ) -> bool {
+        let state = &mut self.params[channel as usize & 0x0F];
+
+        if matches!(control, NRPN_MSB | NRPN_LSB | RPN_MSB | RPN_LSB) {
+            let previous = state.active_control();
+            match control {
+                NRPN_MSB => {
+                    state.nrpn.0 = value;
+                    state.active = Some(ParamKind::Nrpn);
+                }
+                NRPN_LSB => {
+                    state.nrpn.1 = value;
+                    state.active = Some(ParamKind::Nrpn);
+                }
+                _ => {
+                    if control == RPN_MSB {
+                        state.rpn.0 = value;
+                    } else {
+                        state.rpn.1 = value;
+                    }
+                    // RPN 127/127 is the "null" parameter that deselects data entry
+                    state.active = if state.rpn == (127, 127) {
+                        None
+                    } else {
+                        Some(ParamKind::Rpn)
+                    };
+                }
+            }
+            // Data entry LSB and increments must not build on another parameter's value
+            if state.active_control() != previous {
+                state.value = 0;
+            }
+            return true;
+        }
+
+        let Some(param) = state.active_control() else {
+            return false;
+        };
+
+        state.value = match control {
+            DATA_ENTRY_MSB => crate::combine_14bit(value, 0),
+            DATA_ENTRY_LSB => (state.value & !0x7F) | value as u16,
+            DATA_INCREMENT => (state.value + 1).min(MAX_14BIT as u16),
+            DATA_DECREMENT => state.value.saturating_sub(1),
+            _ => return false,
+        };
+
+        events.push(ControlEvent {
+            device: self.device,
+            channel,
+            control: param,
+            value: state.value as f32 / MAX_14BIT,
+        });
+        true
+    }
+}
+
+/// Normalize a 14-bit pitch bend so the centre (8192) lands on exactly 0.5
+///
+/// The two halves have different sizes (8192 steps down, 8191 up), so each is scaled
+/// separately to keep a symmetric range like -1.0..1.0 at rest on 0.0.
+fn normalize_pitch_bend(value: u16) -> f32 {
+    const CENTRE: u16 = 8192;
+    if value >= CENTRE {
+        0.5 + (value - CENTRE) as f32 / (MAX_14BIT - CENTRE as f32) * 0.5
+    } else {
+        value as f32 / CENTRE as f32 * 0.5
+    }
+}
+
+/// Inverse of `normalize_pitch_bend`
+fn denormalize_pitch_bend(normalized: f32) -> u16 {
+    const CENTRE: f32 = 8192.0;
+    if normalized >= 0.5 {
+        (CENTRE + (normalized - 0.5) * 2.0 * (MAX_14BIT - CENTRE)).round() as u16
+    } else {
+        (normalized * 2.0 * CENTRE).round() as u16
+    }
+}
+
+/// Messages that move a control to a normalized value (0.0-1.0), the inverse of decoding
+///
+/// Used to send feedback to LEDs and motorized faders. Aftertouch cannot be sent back to a
+/// controller, so it produces no messages.
+pub fn encode_control(channel: u8, control: MidiControl, normalized: f32) -> Vec<MidiMessage> {
+    let normalized = normalized.clamp(0.0, 1.0);
+    let value_7bit = (normalized * 127.0).round() as u8;
+    let (msb, lsb) = split_14bit((normalized * MAX_14BIT).round() as u16);
+    let cc = |control: u8, value: u8| MidiMessage::ControlChange {
+        channel,
+        control,
+        value,
+    };
+
+    match control {
+        MidiControl::Cc(control) => vec![cc(control, value_7bit)],
+        MidiControl::HiResCc(control) => vec![cc(control, msb), cc(control + 32, lsb)],
+        MidiControl::Note(note) if value_7bit > 0 => vec![MidiMessage::NoteOn {
+            channel,
+            note,
+            velocity: value_7bit,
+        }],
+        MidiControl::Note(note) => vec![MidiMessage::NoteOff {
+            channel,
+            note,
+            velocity: 0,
+        }],
+        MidiControl::Nrpn(param) | MidiControl::Rpn(param) => {
+            let (select_msb, select_lsb) = if matches!(control, MidiControl::Nrpn(_)) {
+                (NRPN_MSB, NRPN_LSB)
+            } else {
+                (RPN_MSB, RPN_LSB)
+            };
+            let (param_msb, param_lsb) = split_14bit(param);
+            vec![
+                cc(select_msb, param_msb),
+                cc(select_lsb, param_lsb),
+                cc(DATA_ENTRY_MSB, msb),
+                cc(DATA_ENTRY_LSB, lsb),
+            ]
+        }
+        MidiControl::PitchBend => vec![MidiMessage::PitchBend {
+            channel,
+            value: denormalize_pitch_bend(normalized),
+        }],
+        MidiControl::ChannelAftertouch | MidiControl::PolyAftertouch(_) => Vec::new(),
+    }
+}
+
+impl Default for ControlDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cc(channel: u8, control: u8, value: u8) -> MidiMessage {
+        MidiMessage::ControlChange {
+            channel,
+            control,
+            value,
+        }
+    }
+
+    fn param_values(events: &[ControlEvent], control: MidiControl) -> Vec<f32> {
+        events
+            .iter()
+            .filter(|e| e.control == control)
+            .map(|e| e.value)
+            .collect()
+    }
+
+    #[test]
+    fn assembles_nrpn_sequence_into_single_parameter_event() {
+        let mut decoder = ControlDecoder::new();
+        let mut events = Vec::new();
+
+        // NRPN 1024 = MSB 8, LSB 0
+        for message in [cc(0, 99, 8), cc(0, 98, 0), cc(0, 6, 0x40), cc(0, 38, 0x01)] {
+            decoder.decode(&message, &mut events);
+        }
+
+        assert_eq!(
+            param_values(&events, MidiControl::Nrpn(1024)),
+            vec![8192.0 / 16383.0, 8193.0 / 16383.0]
+        );
+        // None of the sequence leaks out as plain CCs
+        assert!(events
+            .iter()
+            .all(|e| matches!(e.control, MidiControl::Nrpn(_))));
+    }
+
+    #[test]
+    fn rpn_null_restores_plain_data_entry_cc() {
+        let mut decoder = ControlDecoder::new();
+        let mut events = Vec::new();
+
+        for message in [cc(2, 101, 0), cc(2, 100, 0), cc(2, 6, 2)] {
+            decoder.decode(&message, &mut events);
+        }
+        assert_eq!(param_values(&events, MidiControl::Rpn(0)), vec![256.0 / 16383.0]);
+
+        events.clear();
+        for message in [cc(2, 101, 127), cc(2, 100, 127), cc(2, 6, 127)] {
+            decoder.decode(&message, &mut events);
+        }
+        assert_eq!(param_values(&events, MidiControl::Cc(6)), vec![1.0]);
+    }
+
+    #[test]
+    fn data_increment_and_decrement_step_the_value() {
+        let mut decoder = ControlDecoder::new();
+        let mut events = Vec::new();
+
+        for message in [cc(0, 99, 0), cc(0, 98, 5), cc(0, 6, 0), cc(0, 96, 0), cc(0, 96, 0), cc(0, 97, 0)] {
+            decoder.decode(&message, &mut events);
+        }
+
+        assert_eq!(
+            param_values(&events, MidiControl::Nrpn(5)),
+            vec![0.0, 1.0 / 16383.0, 2.0 / 16383.0, 1.0 / 16383.0]
+        );
+    }
+
+    #[test]
+    fn selecting_another_parameter_starts_from_zero() {
+        let mut decoder = ControlDecoder::new();
+        let mut events = Vec::new();
+
+        // Set NRPN 1 near the top, then select NRPN 2 and only increment it
+        for message in [cc(0, 99, 0), cc(0, 98, 1), cc(0, 6, 0x7F), cc(0, 98, 2), cc(0, 96, 0)] {
+            decoder.decode(&message, &mut events);
+        }
+
+        assert_eq!(param_values(&events, MidiControl::Nrpn(1)), vec![16256.0 / 16383.0]);
+        assert_eq!(param_values(&events, MidiControl::Nrpn(2)), vec![1.0 / 16383.0]);
+    }
+
+    #[test]
+    fn pitch_bend_is_centre_detented() {
+        let mut decoder = ControlDecoder::new();
+        let mut events = Vec::new();
+
+        for value in [0, 8192, 16383] {
+            decoder.decode(&MidiMessage::PitchBend { channel: 0, value }, &mut events);
+        }
+
+        assert_eq!(param_values(&events, MidiControl::PitchBend), vec![0.0, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn encoded_controls_decode_to_the_same_value() {
+        let controls = [
+            MidiControl::Cc(7),
+            MidiControl::HiResCc(1),
+            MidiControl::Nrpn(300),
+            MidiControl::Rpn(0),
+            MidiControl::PitchBend,
+        ];
+
+        for control in controls {
+            for value in [0.0, 0.5, 1.0] {
+                let mut decoder = ControlDecoder::new();
+                let mut events = Vec::new();
+                for message in encode_control(4, control, value) {
+                    decoder.decode(&message, &mut events);
+                }
+
+                let last = events.iter().rev().find(|event| event.control == control).unwrap();
+                assert_eq!(last.channel, 4);
+                assert!((last.value - value).abs() < 0.01, "{} at {}", control, value);
+            }
+        }
+
+        assert!(encode_control(0, MidiControl::ChannelAftertouch, 1.0).is_empty());
+    }
+
+    #[test]
+    fn poly_aftertouch_is_addressed_per_note() {
+        let mut decoder = ControlDecoder::new();
+        let mut events = Vec::new();
+
+        decoder.decode(
+            &MidiMessage::PolyAftertouch {
+                channel: 9,
+                note: 36,
+                pressure: 127,
+            },
+            &mut events,
+        );
+
+        assert_eq!(param_values(&events, MidiControl::PolyAftertouch(36)), vec![1.0]);
+        assert!(param_values(&events, MidiControl::PolyAftertouch(37)).is_empty());
+    }
+
+    #[test]
+    fn pairs_msb_and_lsb_into_14_bit_value() {
+        let mut decoder = ControlDecoder::new();
+        let mut events = Vec::new();
+
+        decoder.decode(&cc(0, 1, 0x40), &mut events);
+        decoder.decode(&cc(0, 33, 0x01), &mut events);
+
+        let values = param_values(&events, MidiControl::HiResCc(1));
+        assert_eq!(values, vec![8192.0 / 16383.0, 8193.0 / 16383.0]);
+    }
+
+    #[test]
+    fn msb_only_controllers_still_reach_full_range() {
+        let mut decoder = ControlDecoder::new();
+        let mut events = Vec::new();
+
+        decoder.decode(&cc(0, 1, 0x7F), &mut events);
+        decoder.decode(&cc(0, 33, 0x7F), &mut events);
+        decoder.decode(&cc(0, 1, 0x00), &mut events);
+
+        assert_eq!(param_values(&events, MidiControl::HiResCc(1)), vec![16256.0 / 16383.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn hires_state_is_per_channel_and_plain_cc_is_still_reported() {
+        let mut decoder = ControlDecoder::new();
+        let mut events = Vec::new();
+
+        decoder.decode(&cc(0, 1, 0x7F), &mut events);
+        decoder.decode(&cc(1, 33, 0x7F), &mut events);
+
+        let last = events.last().unwrap();
+        assert_eq!(last.channel, 1);
+        assert_eq!(last.value, 127.0 / 16383.0);
+        assert!(events
+            .iter()
+            .any(|e| e.control == MidiControl::Cc(33) && e.channel == 1));
+    }
+}
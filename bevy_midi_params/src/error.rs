@@ -1,12 +1,14 @@
 use std::fmt;
 
 /// Errors that can occur in bevy_midi_params
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum MidiError {
     /// No MIDI input ports found
     NoInputPorts,
     /// Failed to connect to MIDI device
     ConnectionFailed(String),
+    /// The MIDI backend did not finish connecting in time
+    ConnectionTimeout(std::time::Duration),
     /// Failed to save/load persistence file
     PersistenceError(String),
     /// Invalid MIDI mapping configuration
@@ -18,6 +20,9 @@ impl fmt::Display for MidiError {
         match self {
             MidiError::NoInputPorts => write!(f, "No MIDI input ports found"),
             MidiError::ConnectionFailed(msg) => write!(f, "MIDI connection failed: {}", msg),
+            MidiError::ConnectionTimeout(timeout) => {
+                write!(f, "MIDI connection timed out after {:.1}s", timeout.as_secs_f32())
+            }
             MidiError::PersistenceError(msg) => write!(f, "Persistence error: {}", msg),
             MidiError::InvalidMapping(msg) => write!(f, "Invalid MIDI mapping: {}", msg),
         }
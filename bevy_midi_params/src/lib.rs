@@ -34,27 +34,55 @@
 //! }
 //! ```
 
+#[cfg(feature = "midi")]
+mod connector;
+#[cfg(feature = "midi")]
 mod controller;
+mod components;
+mod curve;
+mod decoder;
 mod mapping;
+mod message;
 mod persistence;
 mod plugin;
 mod error;
 mod persistence_plugin;
 mod midi_plugin;
+#[cfg(feature = "midi")]
+mod sink;
+#[cfg(feature = "midi")]
+mod smoothing;
+#[cfg(feature = "midi")]
+mod source;
 
 #[cfg(feature = "ui")]
 mod ui;
 
+#[cfg(feature = "midi")]
+pub mod testing;
+
 // Re-export everything users need
-pub use bevy_midi_params_derive::MidiParams;
+pub use bevy_midi_params_derive::{MidiEnum, MidiParams};
+#[cfg(feature = "midi")]
+pub use connector::*;
 #[cfg(feature = "midi")]
 pub use controller::*;
+pub use components::*;
+pub use curve::*;
+pub use decoder::*;
 pub use mapping::*;
+pub use message::*;
 pub use persistence::*;
 pub use plugin::*;
 pub use error::*;
 pub use persistence_plugin::*;
 pub use midi_plugin::*;
+#[cfg(feature = "midi")]
+pub use sink::*;
+#[cfg(feature = "midi")]
+pub use smoothing::*;
+#[cfg(feature = "midi")]
+pub use source::*;
 
 #[cfg(feature = "ui")]
 pub use ui::*;
@@ -62,20 +90,79 @@ pub use ui::*;
 // For auto-registration
 pub use inventory;
 
+// For the serde impls generated by `#[derive(MidiEnum)]`
+#[doc(hidden)]
+pub use serde;
+
+// For the UI generated by the derive
+#[cfg(feature = "ui")]
+pub use bevy_egui::egui;
+
+// Feature gates for code generated by `#[derive(MidiParams)]`. A `#[cfg(feature = ...)]` in the
+// generated code would test the features of the crate using the derive, not of this crate.
+#[cfg(feature = "midi")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __if_midi {
+    ($($item:tt)*) => { $($item)* };
+}
+
+#[cfg(not(feature = "midi"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __if_midi {
+    ($($item:tt)*) => {};
+}
+
+#[cfg(feature = "ui")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __if_ui {
+    ($($item:tt)*) => { $($item)* };
+}
+
+#[cfg(not(feature = "ui"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __if_ui {
+    ($($item:tt)*) => {};
+}
+
+#[cfg(feature = "ui")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __if_not_ui {
+    ($($item:tt)*) => {};
+}
+
+#[cfg(not(feature = "ui"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __if_not_ui {
+    ($($item:tt)*) => { $($item)* };
+}
+
 /// Prelude module for easy imports
 pub mod prelude {
+    #[allow(deprecated)]
     pub use crate::{
+        MidiEnum,
         MidiParams,
         MidiParamsPlugin, // Legacy plugin (deprecated)
         ParamsPersistencePlugin,
         MidiControlPlugin,
         MidiMapping,
+        MidiMessage,
         MidiError,
+        MidiTriggered,
         PersistableParams,
     };
     
     #[cfg(feature = "midi")]
-    pub use crate::MidiController;
+    pub use crate::{
+        MidiConnectionState, MidiController, MidiDeviceConnected, MidiDeviceDisconnected,
+        MidiMessageEvent, MidiSmoothing,
+    };
     
     #[cfg(feature = "ui")]
     pub use crate::ui::*;
@@ -1,70 +1,865 @@
+use crate::{encode_control, MidiMessage, ResponseCurve};
+use std::fmt;
+
+/// Physical control a mapping listens to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MidiControl {
+    /// Control Change number (0-127)
+    Cc(u8),
+    /// 14-bit Control Change pair, MSB on CC n (0-31) and LSB on CC n+32
+    HiResCc(u8),
+    /// Note number (0-127), driven by Note On/Off
+    Note(u8),
+    /// Non-Registered Parameter Number (0-16383) with a 14-bit value
+    Nrpn(u16),
+    /// Registered Parameter Number (0-16383) with a 14-bit value
+    Rpn(u16),
+    /// 14-bit pitch bend wheel, the centre detent maps to exactly 0.5
+    PitchBend,
+    /// Channel-wide pressure (channel aftertouch)
+    ChannelAftertouch,
+    /// Per-note pressure (polyphonic aftertouch) for the given note
+    PolyAftertouch(u8),
+}
+
+impl fmt::Display for MidiControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MidiControl::Cc(cc) => write!(f, "CC{}", cc),
+            MidiControl::HiResCc(cc) => write!(f, "CC{}/{} (14-bit)", cc, cc + 32),
+            MidiControl::Note(note) => write!(f, "Note {}", note),
+            MidiControl::Nrpn(param) => write!(f, "NRPN {}", param),
+            MidiControl::Rpn(param) => write!(f, "RPN {}", param),
+            MidiControl::PitchBend => write!(f, "Pitch Bend"),
+            MidiControl::ChannelAftertouch => write!(f, "Aftertouch"),
+            MidiControl::PolyAftertouch(note) => write!(f, "Poly Aftertouch {}", note),
+        }
+    }
+}
+
+/// Input from a single control received since the previous frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ControlEvent {
+    /// Index of the sending device in `MidiController::devices`
+    pub device: usize,
+    /// MIDI channel (0-15)
+    pub channel: u8,
+    /// Control that produced the input
+    pub control: MidiControl,
+    /// Normalized value (0.0-1.0): the CC value, note velocity (0.0 on Note Off), pressure
+    /// or bend position
+    pub value: f32,
+}
+
 /// MIDI control mapping information
 #[derive(Debug, Clone, PartialEq)]
 pub struct MidiMapping {
-    /// MIDI CC number (0-127), None means persist-only (no MIDI control)
-    pub cc: Option<u8>,
+    /// MIDI control (CC, note, NRPN, pitch bend, ...), None means persist-only (no MIDI control)
+    pub control: Option<MidiControl>,
+    /// MIDI channel (0-15), None means omni (responds on every channel)
+    pub channel: Option<u8>,
+    /// Device name pattern (case-insensitive partial match), None means any device
+    pub device: Option<String>,
     /// Field name this maps to
     pub field_name: String,
-    /// Control type ("Range" or "Button")
+    /// Control type (range, toggle, momentary, trigger, radio button or enum choice)
     pub control_type: ControlType,
     /// Minimum value for range controls
     pub min_value: f32,
-    /// Maximum value for range controls  
+    /// Maximum value for range controls
     pub max_value: f32,
+    /// How an absolute control takes over a range whose value it does not match
+    pub takeover: Takeover,
+    /// Endless encoder settings, None for absolute controls
+    pub relative: Option<RelativeEncoder>,
+    /// Shape between the control's position and a range field
+    pub curve: ResponseCurve,
+    /// Seconds a range field takes to glide (95% of the way) to a new MIDI value, None jumps
+    pub smoothing: Option<f32>,
+    /// Granularity of range values (1.0 for integer fields), 0.0 is continuous
+    pub step: f32,
+}
+
+/// How an absolute control (knob, fader) takes over a range field whose value differs from the
+/// control's physical position, e.g. after values were loaded or changed in the UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Takeover {
+    /// The field jumps to the control's position as soon as it moves
+    #[default]
+    Jump,
+    /// The field is left alone until the control reaches or crosses its value
+    Pickup,
+    /// The field moves in the control's direction, proportionally to the travel left, so both
+    /// meet at the end of the range
+    ValueScaling,
+}
+
+/// How an endless encoder encodes increments in a 7-bit CC value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelativeMode {
+    /// 1..=63 turn up, 127 down to 64 turn down (127 is -1)
+    TwosComplement,
+    /// 64 is at rest, 65 is +1 and 63 is -1
+    BinaryOffset,
+    /// Bit 6 set turns down, the low 6 bits give the amount (65 is -1)
+    SignMagnitude,
+}
+
+impl RelativeMode {
+    /// Signed number of detents in a raw 7-bit value
+    pub fn delta(self, raw: u8) -> i32 {
+        let raw = (raw & 0x7F) as i32;
+        match self {
+            RelativeMode::TwosComplement if raw >= 64 => raw - 128,
+            RelativeMode::TwosComplement => raw,
+            RelativeMode::BinaryOffset => raw - 64,
+            RelativeMode::SignMagnitude if raw & 0x40 != 0 => -(raw & 0x3F),
+            RelativeMode::SignMagnitude => raw,
+        }
+    }
+}
+
+/// Settings of an endless encoder that nudges a range field instead of setting it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RelativeEncoder {
+    pub mode: RelativeMode,
+    /// Field change per detent
+    pub step: f32,
+    /// Extra weight for the larger increments encoders send when turned fast, 0.0 is linear
+    pub acceleration: f32,
+}
+
+impl RelativeEncoder {
+    /// Field change for a raw 7-bit value
+    ///
+    /// An increment of `n` detents moves `n^(1 + acceleration)` steps.
+    pub fn offset(&self, raw: u8) -> f32 {
+        let delta = self.mode.delta(raw) as f32;
+        delta.signum() * delta.abs().powf(1.0 + self.acceleration) * self.step
+    }
+}
+
+/// Distance (normalized) at which a control is considered to have reached the field's value
+const PICKUP_THRESHOLD: f32 = 2.0 / 127.0;
+
+/// Soft takeover state of a single control, see `MidiMapping::takeover_value`
+#[derive(Debug, Clone, Default)]
+pub struct TakeoverState {
+    /// Last position received from the control (normalized)
+    position: Option<f32>,
+    /// Normalized field value after the last update from this control
+    applied: Option<f32>,
+    /// The control has caught up with the field and drives it directly
+    engaged: bool,
+}
+
+impl TakeoverState {
+    /// Normalized value the field should take for a control position, None to leave it alone
+    ///
+    /// `current` is the field's normalized value. The control is released again whenever the
+    /// field has been changed by something else in the meantime.
+    pub fn resolve(&mut self, mode: Takeover, current: f32, position: f32) -> Option<f32> {
+        let previous = self.position.replace(position);
+        if mode == Takeover::Jump {
+            return Some(position);
+        }
+
+        if self
+            .applied
+            .is_none_or(|applied| (applied - current).abs() > 1e-4)
+        {
+            self.engaged = false;
+        }
+
+        if !self.engaged {
+            let crossed = previous
+                .is_some_and(|previous| (previous - current).signum() != (position - current).signum());
+            self.engaged = crossed || (position - current).abs() <= PICKUP_THRESHOLD;
+        }
+
+        let value = if self.engaged {
+            position
+        } else if let (Takeover::ValueScaling, Some(previous)) = (mode, previous) {
+            scale_towards(current, previous, position)
+        } else {
+            return None;
+        };
+
+        self.applied = Some(value);
+        Some(value)
+    }
+}
+
+/// Move `current` by the control's travel from `previous` to `position`, scaled so both reach the
+/// end of the range at the same time
+fn scale_towards(current: f32, previous: f32, position: f32) -> f32 {
+    let value = if position > previous && previous < 1.0 {
+        current + (position - previous) * (1.0 - current) / (1.0 - previous)
+    } else if position < previous && previous > 0.0 {
+        current - (previous - position) * current / previous
+    } else {
+        current
+    };
+    value.clamp(0.0, 1.0)
 }
 
 /// Type of MIDI control
 #[derive(Debug, Clone, PartialEq)]
 pub enum ControlType {
-    /// Continuous range control (knobs, faders)
+    /// Continuous range control (knobs, faders, or note velocity)
     Range { min: f32, max: f32 },
-    /// Toggle button control
+    /// Toggle button control, flips on press
     Button,
+    /// Momentary button control, true only while held
+    Momentary,
+    /// One-shot button that fires a `MidiTriggered` event on press instead of changing a field
+    Trigger,
+    /// One button of a radio group, selects the field value at `index` on press
+    Radio { index: usize },
+    /// Selects one of `count` enum variants, in equal zones of a knob or, with `cycle`, the next
+    /// variant on every press of a button
+    Choice { count: usize, cycle: bool },
+}
+
+/// Field types a radio group of buttons can select from
+///
+/// Implemented for every integer type, where the button at index `n` selects the value `n`.
+pub trait RadioValue: Sized {
+    /// Value selected by the button at `index` in the group, None if there is no such value
+    fn from_radio_index(index: usize) -> Option<Self>;
+
+    /// Index of the button that shows this value, None if no button does
+    fn radio_index(&self) -> Option<usize>;
+}
+
+macro_rules! impl_radio_value {
+    ($($ty:ty),*) => {
+        $(
+            impl RadioValue for $ty {
+                fn from_radio_index(index: usize) -> Option<Self> {
+                    Self::try_from(index).ok()
+                }
+
+                fn radio_index(&self) -> Option<usize> {
+                    usize::try_from(*self).ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_radio_value!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+/// Field types a range can drive
+///
+/// Implemented for `f32`, `f64` and every integer type. Integers round to the nearest whole
+/// number and saturate at the bounds of the type.
+pub trait RangeValue: Copy + PartialEq {
+    /// Granularity of the type's values (see `MidiMapping::with_step`), 0.0 when continuous
+    const STEP: f32;
+
+    /// Field value for a scaled MIDI value
+    fn from_range_value(value: f32) -> Self;
+
+    /// Field value as a scaled MIDI value, the inverse of `from_range_value`
+    fn range_value(&self) -> f32;
+
+    /// Set the field from a scaled MIDI value, returns true if it changed
+    fn set_range_value(&mut self, value: f32) -> bool {
+        let value = Self::from_range_value(value);
+        let changed = *self != value;
+        *self = value;
+        changed
+    }
+}
+
+impl RangeValue for f32 {
+    const STEP: f32 = 0.0;
+
+    fn from_range_value(value: f32) -> Self {
+        value
+    }
+
+    fn range_value(&self) -> f32 {
+        *self
+    }
+
+    fn set_range_value(&mut self, value: f32) -> bool {
+        if (*self - value).abs() > f32::EPSILON {
+            *self = value;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl RangeValue for f64 {
+    const STEP: f32 = 0.0;
+
+    fn from_range_value(value: f32) -> Self {
+        value as f64
+    }
+
+    fn range_value(&self) -> f32 {
+        *self as f32
+    }
+}
+
+macro_rules! impl_integer_range_value {
+    ($($ty:ty),*) => {
+        $(
+            impl RangeValue for $ty {
+                const STEP: f32 = 1.0;
+
+                fn from_range_value(value: f32) -> Self {
+                    // Float to integer casts saturate
+                    value.round() as Self
+                }
+
+                fn range_value(&self) -> f32 {
+                    *self as f32
+                }
+            }
+        )*
+    };
+}
+
+impl_integer_range_value!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+/// Enums a knob or buttons can select a variant of, see `#[derive(MidiEnum)]`
+///
+/// The derive also implements `RadioValue`, so a radio group can select the variants too, and
+/// `Serialize`/`Deserialize` by variant name, so every field of the enum is saved the same way.
+pub trait MidiEnum: Sized {
+    /// Variant names in declaration order
+    const VARIANTS: &'static [&'static str];
+
+    /// Position of this variant in `VARIANTS`
+    fn variant_index(&self) -> usize;
+
+    /// Variant at `index` in `VARIANTS`, None past the last one
+    fn from_variant_index(index: usize) -> Option<Self>;
+
+    /// Name of this variant, as stored in the persistence file
+    fn variant_name(&self) -> &'static str {
+        Self::VARIANTS[self.variant_index()]
+    }
+
+    /// Variant with the given name, None if there is no such variant
+    fn from_variant_name(name: &str) -> Option<Self> {
+        Self::VARIANTS
+            .iter()
+            .position(|variant| *variant == name)
+            .and_then(Self::from_variant_index)
+    }
+
+    /// The variant after this one, wrapping around to the first
+    fn next_variant(&self) -> Option<Self> {
+        Self::from_variant_index((self.variant_index() + 1) % Self::VARIANTS.len())
+    }
 }
 
 impl MidiMapping {
     /// Create a new range mapping
-    pub fn range(cc: Option<u8>, field_name: impl Into<String>, min: f32, max: f32) -> Self {
+    pub fn range(
+        control: Option<MidiControl>,
+        field_name: impl Into<String>,
+        min: f32,
+        max: f32,
+    ) -> Self {
         Self {
-            cc,
+            control,
+            channel: None,
+            device: None,
             field_name: field_name.into(),
             control_type: ControlType::Range { min, max },
             min_value: min,
             max_value: max,
+            takeover: Takeover::Jump,
+            relative: None,
+            curve: ResponseCurve::Linear,
+            smoothing: None,
+            step: 0.0,
         }
     }
-    
+
     /// Create a new button mapping
-    pub fn button(cc: Option<u8>, field_name: impl Into<String>) -> Self {
+    pub fn button(control: Option<MidiControl>, field_name: impl Into<String>) -> Self {
         Self {
-            cc,
+            control,
+            channel: None,
+            device: None,
             field_name: field_name.into(),
             control_type: ControlType::Button,
             min_value: 0.0,
             max_value: 1.0,
+            takeover: Takeover::Jump,
+            relative: None,
+            curve: ResponseCurve::Linear,
+            smoothing: None,
+            step: 0.0,
+        }
+    }
+
+    /// Create a new momentary button mapping
+    pub fn momentary(control: Option<MidiControl>, field_name: impl Into<String>) -> Self {
+        Self {
+            control_type: ControlType::Momentary,
+            ..Self::button(control, field_name)
+        }
+    }
+
+    /// Create a new trigger mapping, pressing the control fires a `MidiTriggered` event
+    pub fn trigger(control: Option<MidiControl>, field_name: impl Into<String>) -> Self {
+        Self {
+            control_type: ControlType::Trigger,
+            ..Self::button(control, field_name)
+        }
+    }
+
+    /// Create a mapping for the button at `index` of a radio group
+    ///
+    /// Every button of the group gets its own mapping to the same field.
+    pub fn radio(control: Option<MidiControl>, field_name: impl Into<String>, index: usize) -> Self {
+        Self {
+            control_type: ControlType::Radio { index },
+            ..Self::button(control, field_name)
+        }
+    }
+
+    /// Create a mapping that selects one of `count` enum variants with a knob
+    ///
+    /// The knob's travel is split into `count` equal zones, one per variant.
+    pub fn choice(control: Option<MidiControl>, field_name: impl Into<String>, count: usize) -> Self {
+        Self {
+            control_type: ControlType::Choice { count, cycle: false },
+            ..Self::range(control, field_name, 0.0, count.saturating_sub(1) as f32)
         }
     }
-    
+
+    /// Create a mapping that moves to the next of `count` enum variants on every press
+    pub fn cycle(control: Option<MidiControl>, field_name: impl Into<String>, count: usize) -> Self {
+        Self {
+            control_type: ControlType::Choice { count, cycle: true },
+            ..Self::choice(control, field_name, count)
+        }
+    }
+
     /// Create a persist-only range mapping (no MIDI control)
     pub fn persist_range(field_name: impl Into<String>, min: f32, max: f32) -> Self {
         Self::range(None, field_name, min, max)
     }
-    
+
     /// Create a persist-only button mapping (no MIDI control)
     pub fn persist_button(field_name: impl Into<String>) -> Self {
         Self::button(None, field_name)
     }
-    
+
+    /// Restrict this mapping to a single MIDI channel (0-15)
+    pub fn with_channel(mut self, channel: u8) -> Self {
+        self.channel = Some(channel);
+        self
+    }
+
+    /// Restrict this mapping to devices whose name contains `pattern` (case-insensitive)
+    pub fn with_device(mut self, pattern: impl Into<String>) -> Self {
+        self.device = Some(pattern.into());
+        self
+    }
+
+    /// Set how the control takes over a field whose value differs from its position
+    pub fn with_takeover(mut self, takeover: Takeover) -> Self {
+        self.takeover = takeover;
+        self
+    }
+
+    /// Treat the control as an endless encoder that moves the field by `step` per detent
+    pub fn with_relative(mut self, mode: RelativeMode, step: f32) -> Self {
+        self.relative = Some(RelativeEncoder {
+            mode,
+            step,
+            acceleration: 0.0,
+        });
+        self
+    }
+
+    /// Make fast encoder turns move further, see `RelativeEncoder::offset`
+    ///
+    /// Has no effect unless `with_relative` has been called.
+    pub fn with_acceleration(mut self, acceleration: f32) -> Self {
+        if let Some(relative) = &mut self.relative {
+            relative.acceleration = acceleration;
+        }
+        self
+    }
+
+    /// Shape how a range follows the control, e.g. for finer control at the low end
+    pub fn with_curve(mut self, curve: ResponseCurve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    /// Glide to new MIDI values over `seconds` instead of jumping, to hide 7-bit stepping
+    pub fn with_smoothing(mut self, seconds: f32) -> Self {
+        self.smoothing = Some(seconds);
+        self
+    }
+
+    /// Snap range values to multiples of `step` from the minimum, e.g. 1.0 for integer fields
+    ///
+    /// Knobs move through the values in equal zones, encoders and smoothing move at least one
+    /// step at a time.
+    pub fn with_step(mut self, step: f32) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// Round a range value to the nearest step, unchanged for continuous mappings
+    pub fn snap_value(&self, value: f32) -> f32 {
+        if self.step <= 0.0 {
+            return value;
+        }
+        let snapped = self.min_value + ((value - self.min_value) / self.step).round() * self.step;
+        snapped.clamp(self.min_value, self.max_value)
+    }
+
+    /// Step whose zone of the range contains a knob value, unchanged for continuous mappings
+    ///
+    /// Like choices, the range is split into one equal zone per step, the last zone ending at
+    /// the maximum.
+    fn zone_value(&self, value: f32) -> f32 {
+        let span = self.max_value - self.min_value;
+        if self.step <= 0.0 || span <= 0.0 {
+            return self.snap_value(value);
+        }
+        // Tolerate float error in the step count, e.g. 0.1 steps over 0.0..1.0
+        let steps = (span / self.step + 1e-4).floor();
+        let zone = ((value - self.min_value) / span * (steps + 1.0)).floor();
+        (self.min_value + zone.clamp(0.0, steps) * self.step).min(self.max_value)
+    }
+
+    /// Check if this mapping accepts input from the named device
+    pub fn matches_device(&self, device_name: &str) -> bool {
+        self.device
+            .as_ref()
+            .is_none_or(|pattern| device_name.to_lowercase().contains(&pattern.to_lowercase()))
+    }
+
+    /// Check if this mapping responds to a control received from a device on the given channel
+    pub fn responds_to(&self, device_name: &str, channel: u8, control: MidiControl) -> bool {
+        self.control == Some(control)
+            && self.channel.is_none_or(|c| c == channel)
+            && self.matches_device(device_name)
+    }
+
     /// Check if this mapping has MIDI control enabled
     pub fn has_midi_control(&self) -> bool {
-        self.cc.is_some()
+        self.control.is_some()
     }
-    
-    /// Scale a normalized MIDI value (0.0-1.0) to this mapping's range
+
+    /// Scale a normalized MIDI value (0.0-1.0) to this mapping's range, following its curve
+    ///
+    /// Buttons give 0.0 or 1.0, radio buttons give their index while pressed.
     pub fn scale_value(&self, normalized: f32) -> f32 {
         match self.control_type {
-            ControlType::Range { min, max } => min + normalized * (max - min),
-            ControlType::Button => if normalized > 0.5 { 1.0 } else { 0.0 },
+            ControlType::Range { min, max } => self.zone_value(self.curve.scale(normalized, min, max)),
+            ControlType::Button | ControlType::Momentary | ControlType::Trigger => {
+                if normalized > 0.5 { 1.0 } else { 0.0 }
+            }
+            ControlType::Radio { index } => {
+                if normalized > 0.5 { index as f32 } else { 0.0 }
+            }
+            ControlType::Choice { cycle: true, .. } => {
+                if normalized > 0.5 { 1.0 } else { 0.0 }
+            }
+            ControlType::Choice { count, cycle: false } => {
+                ((normalized * count as f32) as usize).min(count.saturating_sub(1)) as f32
+            }
+        }
+    }
+
+    /// Map a field value back to a normalized MIDI value (0.0-1.0), the inverse of `scale_value`
+    pub fn normalize_value(&self, value: f32) -> f32 {
+        match self.control_type {
+            ControlType::Range { min, max } => self.curve.normalize(value, min, max),
+            ControlType::Button | ControlType::Momentary | ControlType::Trigger => {
+                if value > 0.5 { 1.0 } else { 0.0 }
+            }
+            // Only the button of the selected value lights up
+            ControlType::Radio { index } => {
+                if value.round() == index as f32 { 1.0 } else { 0.0 }
+            }
+            // Cycle buttons light up on every variant but the first
+            ControlType::Choice { cycle: true, .. } => {
+                if value > 0.5 { 1.0 } else { 0.0 }
+            }
+            // The middle of the variant's zone
+            ControlType::Choice { count, cycle: false } => {
+                ((value.round() + 0.5) / count.max(1) as f32).clamp(0.0, 1.0)
+            }
+        }
+    }
+
+    /// Messages that move the physical control to a field value (LEDs, motorized faders)
+    ///
+    /// Sent on the mapping's channel, or channel 0 for omni mappings. Empty for persist-only
+    /// mappings and controls that cannot receive feedback.
+    pub fn feedback_messages(&self, value: f32) -> Vec<MidiMessage> {
+        let Some(control) = self.control else {
+            return Vec::new();
+        };
+        encode_control(self.channel.unwrap_or(0), control, self.normalize_value(value))
+    }
+
+    /// Value to hand to the params type for an event, or None if the event should be ignored
+    ///
+    /// Ranges receive the scaled value, buttons receive the normalized value. Triggers and cycle
+    /// buttons only report presses, radio buttons report their index on press and choice knobs
+    /// the index of the variant under them.
+    pub fn midi_value(&self, event: &ControlEvent) -> Option<f32> {
+        match self.control_type {
+            ControlType::Range { .. } => {
+                // Note Off carries no position, only Note On velocity moves a range
+                if matches!(event.control, MidiControl::Note(_)) && event.value <= 0.0 {
+                    return None;
+                }
+                Some(self.scale_value(event.value))
+            }
+            ControlType::Button | ControlType::Momentary => Some(event.value),
+            ControlType::Trigger | ControlType::Choice { cycle: true, .. } => {
+                (event.value > 0.5).then_some(event.value)
+            }
+            ControlType::Choice { cycle: false, .. } => Some(self.scale_value(event.value)),
+            ControlType::Radio { .. } => {
+                (event.value > 0.5).then(|| self.scale_value(event.value))
+            }
         }
     }
+
+    /// New field value for an increment from an endless encoder, None for absolute mappings
+    ///
+    /// The raw 7-bit value is recovered from the event and `current` is moved within the range.
+    pub fn relative_value(&self, event: &ControlEvent, current: f32) -> Option<f32> {
+        let relative = self.relative?;
+        let ControlType::Range { min, max } = self.control_type else {
+            return None;
+        };
+
+        let raw = (event.value * 127.0).round() as u8;
+        let offset = relative.offset(raw);
+        // Every detent moves at least one step, smaller steps would round back to `current`
+        let offset = if offset != 0.0 {
+            offset.signum() * offset.abs().max(self.step)
+        } else {
+            0.0
+        };
+        Some(self.snap_value((current + offset).clamp(min, max)))
+    }
+
+    /// Like `midi_value`, but applies the takeover mode against the field's current value
+    ///
+    /// Takeover only affects ranges driven by absolute controls; note velocities and buttons
+    /// behave as with `midi_value`.
+    pub fn takeover_value(
+        &self,
+        event: &ControlEvent,
+        current: f32,
+        state: &mut TakeoverState,
+    ) -> Option<f32> {
+        let value = self.midi_value(event)?;
+        if !matches!(self.control_type, ControlType::Range { .. })
+            || matches!(event.control, MidiControl::Note(_))
+        {
+            return Some(value);
+        }
+
+        let normalized = state.resolve(self.takeover, self.normalize_value(current), event.value)?;
+        let value = self.scale_value(normalized);
+        if self.step > 0.0 {
+            // The field holds the snapped value, which must not look like a change made elsewhere
+            state.applied = Some(self.normalize_value(value));
+        }
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(channel: u8, control: MidiControl, value: f32) -> ControlEvent {
+        ControlEvent {
+            device: 0,
+            channel,
+            control,
+            value,
+        }
+    }
+
+    #[test]
+    fn channel_restricted_mapping_ignores_other_channels() {
+        let omni = MidiMapping::range(Some(MidiControl::Cc(7)), "omni", 0.0, 1.0);
+        let ch2 = MidiMapping::range(Some(MidiControl::Cc(7)), "ch2", 0.0, 1.0).with_channel(2);
+
+        assert!(omni.responds_to("Any", 5, MidiControl::Cc(7)));
+        assert!(ch2.responds_to("Any", 2, MidiControl::Cc(7)));
+        assert!(!ch2.responds_to("Any", 3, MidiControl::Cc(7)));
+        assert!(!ch2.responds_to("Any", 2, MidiControl::Note(7)));
+    }
+
+    #[test]
+    fn device_pattern_is_a_case_insensitive_partial_match() {
+        let mapping = MidiMapping::range(Some(MidiControl::Cc(7)), "dimmer", 0.0, 1.0).with_device("midimix");
+
+        assert!(mapping.responds_to("MIDImix:MIDImix MIDI 1 20:0", 0, MidiControl::Cc(7)));
+        assert!(!mapping.responds_to("Launch Control XL", 0, MidiControl::Cc(7)));
+    }
+
+    #[test]
+    fn note_velocity_range_ignores_note_off() {
+        let mapping = MidiMapping::range(Some(MidiControl::Note(36)), "velocity", 0.0, 2.0);
+
+        assert_eq!(mapping.midi_value(&event(0, MidiControl::Note(36), 0.5)), Some(1.0));
+        assert_eq!(mapping.midi_value(&event(0, MidiControl::Note(36), 0.0)), None);
+    }
+
+    #[test]
+    fn feedback_is_the_inverse_of_scaling() {
+        let volume = MidiMapping::range(Some(MidiControl::Cc(7)), "volume", 0.0, 10.0).with_channel(2);
+        let muted = MidiMapping::button(Some(MidiControl::Note(36)), "muted");
+
+        assert_eq!(
+            volume.feedback_messages(5.0),
+            vec![MidiMessage::ControlChange {
+                channel: 2,
+                control: 7,
+                value: 64
+            }]
+        );
+        assert_eq!(volume.normalize_value(20.0), 1.0);
+        assert_eq!(
+            muted.feedback_messages(1.0),
+            vec![MidiMessage::NoteOn {
+                channel: 0,
+                note: 36,
+                velocity: 127
+            }]
+        );
+        assert!(MidiMapping::persist_range("gain", 0.0, 1.0).feedback_messages(0.5).is_empty());
+    }
+
+    #[test]
+    fn pickup_waits_for_the_control_to_cross_the_value() {
+        let mut state = TakeoverState::default();
+
+        assert_eq!(state.resolve(Takeover::Pickup, 0.6, 0.2), None);
+        assert_eq!(state.resolve(Takeover::Pickup, 0.6, 0.4), None);
+        assert_eq!(state.resolve(Takeover::Pickup, 0.6, 0.7), Some(0.7));
+        assert_eq!(state.resolve(Takeover::Pickup, 0.7, 0.5), Some(0.5));
+
+        // Changed elsewhere (UI, presets): released until reached again
+        assert_eq!(state.resolve(Takeover::Pickup, 0.9, 0.6), None);
+        assert_eq!(state.resolve(Takeover::Pickup, 0.9, 0.89), Some(0.89));
+    }
+
+    #[test]
+    fn value_scaling_converges_at_the_end_of_travel() {
+        let mut state = TakeoverState::default();
+
+        // The first touch only tells where the control is
+        assert_eq!(state.resolve(Takeover::ValueScaling, 0.5, 0.0), None);
+        assert_eq!(state.resolve(Takeover::ValueScaling, 0.5, 0.25), Some(0.625));
+        assert_eq!(state.resolve(Takeover::ValueScaling, 0.625, 0.5), Some(0.75));
+        assert_eq!(state.resolve(Takeover::ValueScaling, 0.75, 1.0), Some(1.0));
+        assert_eq!(state.resolve(Takeover::ValueScaling, 1.0, 0.8), Some(0.8));
+    }
+
+    #[test]
+    fn relative_modes_decode_increments() {
+        assert_eq!([1, 63, 127, 64].map(|raw| RelativeMode::TwosComplement.delta(raw)), [1, 63, -1, -64]);
+        assert_eq!([65, 64, 63, 0].map(|raw| RelativeMode::BinaryOffset.delta(raw)), [1, 0, -1, -64]);
+        assert_eq!([1, 65, 70].map(|raw| RelativeMode::SignMagnitude.delta(raw)), [1, -1, -6]);
+    }
+
+    #[test]
+    fn encoders_nudge_within_the_range() {
+        let mapping = MidiMapping::range(Some(MidiControl::Cc(1)), "pan", -1.0, 1.0)
+            .with_relative(RelativeMode::TwosComplement, 0.25);
+        let turn = |raw: u8| event(0, MidiControl::Cc(1), raw as f32 / 127.0);
+
+        assert_eq!(mapping.relative_value(&turn(2), 0.0), Some(0.5));
+        assert_eq!(mapping.relative_value(&turn(127), 0.0), Some(-0.25));
+        assert_eq!(mapping.relative_value(&turn(10), 0.0), Some(1.0));
+
+        let accelerated = mapping.with_acceleration(1.0);
+        assert_eq!(accelerated.relative_value(&turn(1), 0.0), Some(0.25));
+        assert_eq!(accelerated.relative_value(&turn(2), -1.0), Some(0.0));
+    }
+
+    #[test]
+    fn buttons_receive_normalized_value() {
+        let mapping = MidiMapping::momentary(Some(MidiControl::Note(36)), "held");
+
+        assert_eq!(mapping.midi_value(&event(0, MidiControl::Note(36), 0.8)), Some(0.8));
+        assert_eq!(mapping.midi_value(&event(0, MidiControl::Note(36), 0.0)), Some(0.0));
+    }
+
+    #[test]
+    fn radio_buttons_select_their_index_and_light_when_selected() {
+        let third = MidiMapping::radio(Some(MidiControl::Note(38)), "mode", 2);
+        let reset = MidiMapping::trigger(Some(MidiControl::Note(40)), "reset");
+
+        assert_eq!(third.midi_value(&event(0, MidiControl::Note(38), 0.9)), Some(2.0));
+        assert_eq!(third.midi_value(&event(0, MidiControl::Note(38), 0.0)), None);
+        assert_eq!(third.normalize_value(2.0), 1.0);
+        assert_eq!(third.normalize_value(1.0), 0.0);
+        assert_eq!(reset.midi_value(&event(0, MidiControl::Note(40), 0.0)), None);
+        assert_eq!(u8::from_radio_index(300), None);
+        assert_eq!((-1i32).radio_index(), None);
+    }
+
+    #[test]
+    fn choice_knobs_split_their_travel_into_equal_zones() {
+        let knob = MidiMapping::choice(Some(MidiControl::Cc(5)), "tonemapping", 4);
+        let cycle = MidiMapping::cycle(Some(MidiControl::Note(36)), "tonemapping", 4);
+
+        assert_eq!(
+            [0.0, 0.24, 0.26, 0.74, 0.76, 1.0].map(|x| knob.scale_value(x)),
+            [0.0, 0.0, 1.0, 2.0, 3.0, 3.0]
+        );
+        assert_eq!(knob.normalize_value(1.0), 0.375);
+        assert_eq!(cycle.midi_value(&event(0, MidiControl::Note(36), 0.8)), Some(0.8));
+        assert_eq!(cycle.midi_value(&event(0, MidiControl::Note(36), 0.0)), None);
+    }
+
+    #[test]
+    fn integer_ranges_round_and_saturate() {
+        let mut quality = 3i32;
+
+        assert!(quality.set_range_value(6.6));
+        assert_eq!(quality, 7);
+        assert!(!quality.set_range_value(7.2));
+        assert_eq!(u8::from_range_value(-3.0), 0);
+        assert_eq!(u8::from_range_value(300.0), 255);
+        assert_eq!(f64::from_range_value(0.5).range_value(), 0.5);
+    }
+
+    #[test]
+    fn stepped_ranges_split_knobs_into_equal_zones() {
+        let knob = MidiMapping::range(Some(MidiControl::Cc(1)), "quality", 0.0, 10.0).with_step(1.0);
+        let encoder = knob.clone().with_relative(RelativeMode::TwosComplement, 0.1);
+        let turn = |raw: u8| event(0, MidiControl::Cc(1), raw as f32 / 127.0);
+
+        // 11 values, each owns 1/11 of the travel, the ends included
+        assert_eq!(knob.scale_value(0.0), 0.0);
+        assert_eq!(knob.scale_value(0.09), 0.0);
+        assert_eq!(knob.scale_value(0.1), 1.0);
+        assert_eq!(knob.scale_value(0.5), 5.0);
+        assert_eq!(knob.scale_value(0.9), 9.0);
+        assert_eq!(knob.scale_value(0.91), 10.0);
+        assert_eq!(knob.scale_value(1.0), 10.0);
+        assert_eq!(encoder.relative_value(&turn(1), 3.0), Some(4.0));
+        assert_eq!(encoder.relative_value(&turn(127), 0.0), Some(0.0));
+    }
 }
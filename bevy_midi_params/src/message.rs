@@ -0,0 +1,354 @@
+#[cfg(feature = "midi")]
+use bevy::prelude::*;
+
+/// A decoded MIDI message
+///
+/// Channels are 0-15, matching the raw status byte (and the output of `aseqdump`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MidiMessage {
+    /// Note released (also produced by Note On with velocity 0)
+    NoteOff { channel: u8, note: u8, velocity: u8 },
+    /// Note pressed
+    NoteOn { channel: u8, note: u8, velocity: u8 },
+    /// Per-note pressure (polyphonic aftertouch)
+    PolyAftertouch { channel: u8, note: u8, pressure: u8 },
+    /// Control Change
+    ControlChange { channel: u8, control: u8, value: u8 },
+    /// Program Change
+    ProgramChange { channel: u8, program: u8 },
+    /// Channel-wide pressure (channel aftertouch)
+    ChannelAftertouch { channel: u8, pressure: u8 },
+    /// Pitch bend, 14-bit value where 8192 is the centre
+    PitchBend { channel: u8, value: u16 },
+    /// System Exclusive payload, without the 0xF0/0xF7 framing bytes
+    SysEx(Vec<u8>),
+    /// MIDI Time Code quarter frame
+    TimeCodeQuarterFrame(u8),
+    /// Song Position Pointer, in MIDI beats
+    SongPosition(u16),
+    /// Song Select
+    SongSelect(u8),
+    /// Tune Request
+    TuneRequest,
+    /// Realtime: timing clock (24 per quarter note)
+    TimingClock,
+    /// Realtime: start
+    Start,
+    /// Realtime: continue
+    Continue,
+    /// Realtime: stop
+    Stop,
+    /// Realtime: active sensing
+    ActiveSensing,
+    /// Realtime: system reset
+    Reset,
+}
+
+impl MidiMessage {
+    /// Decode a single complete MIDI message, returns None for malformed or unknown data
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let status = *bytes.first()?;
+        let data = |index: usize| bytes.get(index).copied().filter(|b| *b < 0x80);
+
+        if status < 0xF0 {
+            let channel = status & 0x0F;
+            let message = match status & 0xF0 {
+                0x80 => MidiMessage::NoteOff {
+                    channel,
+                    note: data(1)?,
+                    velocity: data(2)?,
+                },
+                0x90 => {
+                    let note = data(1)?;
+                    let velocity = data(2)?;
+                    if velocity == 0 {
+                        MidiMessage::NoteOff {
+                            channel,
+                            note,
+                            velocity,
+                        }
+                    } else {
+                        MidiMessage::NoteOn {
+                            channel,
+                            note,
+                            velocity,
+                        }
+                    }
+                }
+                0xA0 => MidiMessage::PolyAftertouch {
+                    channel,
+                    note: data(1)?,
+                    pressure: data(2)?,
+                },
+                0xB0 => MidiMessage::ControlChange {
+                    channel,
+                    control: data(1)?,
+                    value: data(2)?,
+                },
+                0xC0 => MidiMessage::ProgramChange {
+                    channel,
+                    program: data(1)?,
+                },
+                0xD0 => MidiMessage::ChannelAftertouch {
+                    channel,
+                    pressure: data(1)?,
+                },
+                0xE0 => MidiMessage::PitchBend {
+                    channel,
+                    value: combine_14bit(data(2)?, data(1)?),
+                },
+                // Data byte without a status byte
+                _ => return None,
+            };
+            return Some(message);
+        }
+
+        let message = match status {
+            0xF0 => {
+                let payload = &bytes[1..];
+                let payload = payload.strip_suffix(&[0xF7]).unwrap_or(payload);
+                MidiMessage::SysEx(payload.to_vec())
+            }
+            0xF1 => MidiMessage::TimeCodeQuarterFrame(data(1)?),
+            0xF2 => MidiMessage::SongPosition(combine_14bit(data(2)?, data(1)?)),
+            0xF3 => MidiMessage::SongSelect(data(1)?),
+            0xF6 => MidiMessage::TuneRequest,
+            0xF8 => MidiMessage::TimingClock,
+            0xFA => MidiMessage::Start,
+            0xFB => MidiMessage::Continue,
+            0xFC => MidiMessage::Stop,
+            0xFE => MidiMessage::ActiveSensing,
+            0xFF => MidiMessage::Reset,
+            _ => return None,
+        };
+        Some(message)
+    }
+
+    /// Encode this message as raw MIDI bytes, the inverse of `parse`
+    ///
+    /// Data bytes are masked to 7 bits, so out-of-range values cannot corrupt the stream.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let status = |kind: u8, channel: u8| kind | (channel & 0x0F);
+        match self {
+            MidiMessage::NoteOff {
+                channel,
+                note,
+                velocity,
+            } => vec![status(0x80, *channel), note & 0x7F, velocity & 0x7F],
+            MidiMessage::NoteOn {
+                channel,
+                note,
+                velocity,
+            } => vec![status(0x90, *channel), note & 0x7F, velocity & 0x7F],
+            MidiMessage::PolyAftertouch {
+                channel,
+                note,
+                pressure,
+            } => vec![status(0xA0, *channel), note & 0x7F, pressure & 0x7F],
+            MidiMessage::ControlChange {
+                channel,
+                control,
+                value,
+            } => vec![status(0xB0, *channel), control & 0x7F, value & 0x7F],
+            MidiMessage::ProgramChange { channel, program } => {
+                vec![status(0xC0, *channel), program & 0x7F]
+            }
+            MidiMessage::ChannelAftertouch { channel, pressure } => {
+                vec![status(0xD0, *channel), pressure & 0x7F]
+            }
+            MidiMessage::PitchBend { channel, value } => {
+                let (msb, lsb) = split_14bit(*value);
+                vec![status(0xE0, *channel), lsb, msb]
+            }
+            MidiMessage::SysEx(payload) => {
+                let mut bytes = Vec::with_capacity(payload.len() + 2);
+                bytes.push(0xF0);
+                bytes.extend(payload.iter().map(|b| b & 0x7F));
+                bytes.push(0xF7);
+                bytes
+            }
+            MidiMessage::TimeCodeQuarterFrame(data) => vec![0xF1, data & 0x7F],
+            MidiMessage::SongPosition(position) => {
+                let (msb, lsb) = split_14bit(*position);
+                vec![0xF2, lsb, msb]
+            }
+            MidiMessage::SongSelect(song) => vec![0xF3, song & 0x7F],
+            MidiMessage::TuneRequest => vec![0xF6],
+            MidiMessage::TimingClock => vec![0xF8],
+            MidiMessage::Start => vec![0xFA],
+            MidiMessage::Continue => vec![0xFB],
+            MidiMessage::Stop => vec![0xFC],
+            MidiMessage::ActiveSensing => vec![0xFE],
+            MidiMessage::Reset => vec![0xFF],
+        }
+    }
+
+    /// Channel (0-15) for channel voice messages, None for system messages
+    pub fn channel(&self) -> Option<u8> {
+        match self {
+            MidiMessage::NoteOff { channel, .. }
+            | MidiMessage::NoteOn { channel, .. }
+            | MidiMessage::PolyAftertouch { channel, .. }
+            | MidiMessage::ControlChange { channel, .. }
+            | MidiMessage::ProgramChange { channel, .. }
+            | MidiMessage::ChannelAftertouch { channel, .. }
+            | MidiMessage::PitchBend { channel, .. } => Some(*channel),
+            _ => None,
+        }
+    }
+
+    /// Whether this is a single-byte realtime message (clock, start, stop, ...)
+    pub fn is_realtime(&self) -> bool {
+        matches!(
+            self,
+            MidiMessage::TimingClock
+                | MidiMessage::Start
+                | MidiMessage::Continue
+                | MidiMessage::Stop
+                | MidiMessage::ActiveSensing
+                | MidiMessage::Reset
+        )
+    }
+}
+
+/// Combine a 7-bit MSB and LSB into a 14-bit value
+pub(crate) fn combine_14bit(msb: u8, lsb: u8) -> u16 {
+    ((msb as u16) << 7) | lsb as u16
+}
+
+/// Split a 14-bit value into its 7-bit MSB and LSB
+pub(crate) fn split_14bit(value: u16) -> (u8, u8) {
+    (((value >> 7) & 0x7F) as u8, (value & 0x7F) as u8)
+}
+
+/// Bevy event fired for every MIDI message received from the controller
+#[cfg(feature = "midi")]
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct MidiMessageEvent {
+    /// Name of the input port the message arrived on
+    pub device: String,
+    pub message: MidiMessage,
+}
+
+/// Bevy event fired when a MIDI device is opened, at startup or when it is plugged in
+#[cfg(feature = "midi")]
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct MidiDeviceConnected {
+    /// Name of the input port
+    pub device: String,
+}
+
+/// Bevy event fired when an open MIDI device disappears (unplugged or powered off)
+#[cfg(feature = "midi")]
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct MidiDeviceDisconnected {
+    /// Name of the input port
+    pub device: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_channel_voice_messages() {
+        assert_eq!(
+            MidiMessage::parse(&[0xB3, 7, 100]),
+            Some(MidiMessage::ControlChange {
+                channel: 3,
+                control: 7,
+                value: 100
+            })
+        );
+        assert_eq!(
+            MidiMessage::parse(&[0x91, 60, 90]),
+            Some(MidiMessage::NoteOn {
+                channel: 1,
+                note: 60,
+                velocity: 90
+            })
+        );
+        assert_eq!(
+            MidiMessage::parse(&[0xC0, 5]),
+            Some(MidiMessage::ProgramChange {
+                channel: 0,
+                program: 5
+            })
+        );
+    }
+
+    #[test]
+    fn note_on_with_zero_velocity_is_note_off() {
+        assert_eq!(
+            MidiMessage::parse(&[0x90, 60, 0]),
+            Some(MidiMessage::NoteOff {
+                channel: 0,
+                note: 60,
+                velocity: 0
+            })
+        );
+    }
+
+    #[test]
+    fn pitch_bend_is_14_bit_lsb_first() {
+        assert_eq!(
+            MidiMessage::parse(&[0xE2, 0x00, 0x40]),
+            Some(MidiMessage::PitchBend {
+                channel: 2,
+                value: 8192
+            })
+        );
+        assert_eq!(
+            MidiMessage::parse(&[0xE0, 0x7F, 0x7F]),
+            Some(MidiMessage::PitchBend {
+                channel: 0,
+                value: 16383
+            })
+        );
+    }
+
+    #[test]
+    fn parses_system_messages() {
+        assert_eq!(
+            MidiMessage::parse(&[0xF0, 0x7E, 0x01, 0xF7]),
+            Some(MidiMessage::SysEx(vec![0x7E, 0x01]))
+        );
+        assert_eq!(MidiMessage::parse(&[0xF8]), Some(MidiMessage::TimingClock));
+        assert!(MidiMessage::parse(&[0xFC]).unwrap().is_realtime());
+    }
+
+    #[test]
+    fn encoding_round_trips_through_parse() {
+        let messages = [
+            MidiMessage::NoteOff {
+                channel: 15,
+                note: 60,
+                velocity: 0,
+            },
+            MidiMessage::ControlChange {
+                channel: 3,
+                control: 7,
+                value: 100,
+            },
+            MidiMessage::PitchBend {
+                channel: 2,
+                value: 12345,
+            },
+            MidiMessage::SysEx(vec![0x7E, 0x01]),
+            MidiMessage::SongPosition(300),
+            MidiMessage::TimingClock,
+        ];
+
+        for message in messages {
+            assert_eq!(MidiMessage::parse(&message.to_bytes()), Some(message));
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_and_malformed_messages() {
+        assert_eq!(MidiMessage::parse(&[]), None);
+        assert_eq!(MidiMessage::parse(&[0xB0, 7]), None);
+        assert_eq!(MidiMessage::parse(&[0x90, 0x80, 10]), None);
+        assert_eq!(MidiMessage::parse(&[0x40]), None);
+    }
+}
@@ -1,17 +1,41 @@
 #[cfg(feature = "midi")]
-use crate::{MidiController, PersistableParams, PersistenceController};
+use crate::{
+    ControlType, DeviceChange, MidiConnectionState, MidiController, MidiDeviceConnected,
+    MidiDeviceDisconnected, MidiMessage, MidiMessageEvent, MidiResult, MidiSink, MidiSmoothing,
+    MidiSource, PersistableParams, Takeover, TakeoverState,
+};
+#[cfg(feature = "midi")]
+use crate::smoothing::smooth_params;
+#[cfg(feature = "midi")]
+use bevy::ecs::system::SystemParam;
 #[cfg(feature = "midi")]
 use bevy::prelude::*;
 #[cfg(feature = "midi")]
-use log::{debug, error, info, warn};
+use log::{info, warn};
+#[cfg(feature = "midi")]
+use std::collections::HashMap;
+#[cfg(feature = "midi")]
+use std::sync::Mutex;
+#[cfg(feature = "midi")]
+use std::time::{Duration, Instant};
 
 /// MIDI control plugin for development builds (requires "midi" feature)
 #[cfg(feature = "midi")]
 pub struct MidiControlPlugin {
     /// Whether to auto-connect to MIDI on startup
     pub auto_connect: bool,
-    /// Preferred MIDI controller name (partial match)
-    pub preferred_controller: Option<String>,
+    /// Preferred MIDI controller names (partial match), every matching port is opened
+    pub preferred_controllers: Vec<String>,
+    /// How often to look for plugged/unplugged devices, None disables hot-plug detection
+    pub scan_interval: Option<Duration>,
+    /// How long a connection attempt may take before it is reported as failed
+    pub connect_timeout: Duration,
+    /// Whether field values are sent back to devices (LEDs, motorized faders)
+    pub feedback: bool,
+    /// Custom MIDI transport, taken by the controller when the plugin is built
+    source: Mutex<Option<Box<dyn MidiSource>>>,
+    /// Custom MIDI output transport, taken by the controller when the plugin is built
+    sink: Mutex<Option<Box<dyn MidiSink>>>,
 }
 
 #[cfg(feature = "midi")]
@@ -19,7 +43,12 @@ impl Default for MidiControlPlugin {
     fn default() -> Self {
         Self {
             auto_connect: true,
-            preferred_controller: None,
+            preferred_controllers: Vec::new(),
+            scan_interval: Some(Duration::from_secs(2)),
+            connect_timeout: Duration::from_secs(5),
+            feedback: true,
+            source: Mutex::new(None),
+            sink: Mutex::new(None),
         }
     }
 }
@@ -31,9 +60,11 @@ impl MidiControlPlugin {
         Self::default()
     }
 
-    /// Set preferred MIDI controller name (partial match)
+    /// Add a preferred MIDI controller name (partial match)
+    ///
+    /// Call it once per device to use several controllers at the same time.
     pub fn with_controller(mut self, controller_name: impl Into<String>) -> Self {
-        self.preferred_controller = Some(controller_name.into());
+        self.preferred_controllers.push(controller_name.into());
         self
     }
 
@@ -42,16 +73,64 @@ impl MidiControlPlugin {
         self.auto_connect = false;
         self
     }
+
+    /// Set how often to scan for devices being plugged in or unplugged
+    pub fn with_scan_interval(mut self, interval: Duration) -> Self {
+        self.scan_interval = Some(interval);
+        self
+    }
+
+    /// Set how long a connection attempt may take before it is reported as failed
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Disable hot-plug detection, devices are only connected at startup
+    pub fn no_hot_plug(mut self) -> Self {
+        self.scan_interval = None;
+        self
+    }
+
+    /// Never open output ports or send field values back to devices
+    pub fn no_feedback(mut self) -> Self {
+        self.feedback = false;
+        self
+    }
+
+    /// Read MIDI from a custom transport instead of midir
+    pub fn with_source(self, source: impl MidiSource) -> Self {
+        Self {
+            source: Mutex::new(Some(Box::new(source))),
+            ..self
+        }
+    }
+
+    /// Send feedback through a custom transport instead of midir
+    pub fn with_sink(self, sink: impl MidiSink) -> Self {
+        Self {
+            sink: Mutex::new(Some(Box::new(sink))),
+            ..self
+        }
+    }
 }
 
 #[cfg(feature = "midi")]
 impl Plugin for MidiControlPlugin {
     fn build(&self, app: &mut App) {
         // Insert MIDI controller resource
-        app.insert_resource(MidiController::new(
-            None, // Persistence is handled by PersistenceController
-            self.preferred_controller.clone(),
-        ));
+        let mut midi_controller = self.preferred_controllers.iter().fold(
+            MidiController::new(None, None), // Persistence is handled by PersistenceController
+            |controller, name| controller.with_controller(name.clone()),
+        );
+        if let Some(source) = self.source.lock().ok().and_then(|mut source| source.take()) {
+            midi_controller.set_source(source);
+        }
+        if let Some(sink) = self.sink.lock().ok().and_then(|mut sink| sink.take()) {
+            midi_controller.set_sink(sink);
+        }
+        midi_controller.set_feedback(self.feedback);
+        app.insert_resource(midi_controller);
 
         // Register MIDI mappings for all registered types
         for registration in inventory::iter::<crate::ParamsRegistration> {
@@ -59,8 +138,25 @@ impl Plugin for MidiControlPlugin {
             // This will be handled by the register_midi_mappings system
         }
 
+        app.add_event::<MidiMessageEvent>()
+            .add_event::<MidiDeviceConnected>()
+            .add_event::<MidiDeviceDisconnected>();
+
+        app.insert_resource(if self.auto_connect {
+            MidiConnectionState::Connecting
+        } else {
+            MidiConnectionState::Disconnected
+        });
+
         if self.auto_connect {
-            app.add_systems(Startup, setup_midi_input);
+            // The first scan connects, off the main thread so startup never waits on the backend
+            app.world_mut().resource_mut::<MidiController>().request_scan();
+            app.insert_resource(MidiDeviceScanner {
+                interval: self.scan_interval,
+                connect_timeout: self.connect_timeout,
+                last_scan: Instant::now(),
+            });
+            app.add_systems(PreUpdate, scan_midi_devices.before(update_midi_controller));
         }
 
         app.add_systems(PreUpdate, update_midi_controller);
@@ -68,18 +164,107 @@ impl Plugin for MidiControlPlugin {
     }
 }
 
+/// Hot-plug scanning state
+#[cfg(feature = "midi")]
+#[derive(Resource)]
+struct MidiDeviceScanner {
+    interval: Option<Duration>,
+    connect_timeout: Duration,
+    last_scan: Instant,
+}
+
+/// Everything a device refresh reports to
+#[cfg(feature = "midi")]
+#[derive(SystemParam)]
+struct DeviceReports<'w> {
+    state: ResMut<'w, MidiConnectionState>,
+    connected: EventWriter<'w, MidiDeviceConnected>,
+    disconnected: EventWriter<'w, MidiDeviceDisconnected>,
+}
+
+#[cfg(feature = "midi")]
+impl DeviceReports<'_> {
+    /// Report the result of a device refresh as events and a state change
+    fn report(&mut self, result: MidiResult<Vec<DeviceChange>>, connected: &[String]) {
+        let state = match result {
+            Ok(changes) => {
+                for change in changes {
+                    match change {
+                        DeviceChange::Connected(device) => {
+                            info!("MIDI connection established: {}", device);
+                            self.connected.write(MidiDeviceConnected { device });
+                        }
+                        DeviceChange::Disconnected(device) => {
+                            self.disconnected.write(MidiDeviceDisconnected { device });
+                        }
+                    }
+                }
+
+                if connected.is_empty() {
+                    MidiConnectionState::Disconnected
+                } else {
+                    MidiConnectionState::Connected(connected.to_vec())
+                }
+            }
+            // Ports that are still open keep working even if new ones failed
+            Err(e) if connected.is_empty() => {
+                warn!("Failed to connect MIDI: {}", e);
+                MidiConnectionState::Failed(e)
+            }
+            Err(e) => {
+                warn!("Failed to connect MIDI: {}", e);
+                MidiConnectionState::Connected(connected.to_vec())
+            }
+        };
+
+        self.state.set_if_neq(state);
+    }
+}
+
 #[cfg(feature = "midi")]
-/// Setup MIDI input connection
-fn setup_midi_input(mut midi_controller: ResMut<MidiController>) {
-    match midi_controller.connect_midi() {
-        Ok(()) => info!("MIDI connection established"),
-        Err(e) => warn!("Failed to connect MIDI: {}", e),
+/// Start device refreshes when due and report their results
+///
+/// Refreshes run in the background, so connecting at startup, reconnecting preferred
+/// devices and noticing unplugged ones never stall a frame.
+fn scan_midi_devices(
+    mut scanner: ResMut<MidiDeviceScanner>,
+    mut midi_controller: ResMut<MidiController>,
+    mut reports: DeviceReports,
+) {
+    let requested = midi_controller.take_scan_request();
+    let due = scanner
+        .interval
+        .is_some_and(|interval| scanner.last_scan.elapsed() >= interval);
+
+    if (requested || due) && !midi_controller.is_refreshing() {
+        scanner.last_scan = Instant::now();
+        if !reports.state.is_connected() {
+            reports.state.set_if_neq(MidiConnectionState::Connecting);
+        }
+        midi_controller.start_refresh();
+    }
+
+    if let Some(result) = midi_controller.poll_refresh(scanner.connect_timeout) {
+        reports.report(result, midi_controller.connected_devices());
     }
 }
 
 #[cfg(feature = "midi")]
-fn update_midi_controller(mut midi_controller: ResMut<MidiController>) {
+fn update_midi_controller(
+    mut midi_controller: ResMut<MidiController>,
+    mut midi_events: EventWriter<MidiMessageEvent>,
+) {
     midi_controller.update_values();
+
+    midi_events.write_batch(
+        midi_controller
+            .messages()
+            .iter()
+            .map(|received| MidiMessageEvent {
+                device: received.port.clone(),
+                message: received.message.clone(),
+            }),
+    );
 }
 
 #[cfg(feature = "midi")]
@@ -96,7 +281,7 @@ fn register_midi_mappings_system(world: &mut World) {
         REGISTERED = true;
     }
 
-    if let Some(mut midi_controller) = world.get_resource_mut::<MidiController>() {
+    if world.contains_resource::<MidiController>() {
         for registration in inventory::iter::<crate::ParamsRegistration> {
             info!("Registering MIDI mappings for: {}", registration.type_name);
             // The actual mapping registration will be handled by each type's registration function
@@ -122,61 +307,190 @@ pub fn register_midi_control<T: Resource + PersistableParams + Default>(app: &mu
         midi_controller.register_type(type_name);
     }
 
-    // Add MIDI update system for this type
-    app.add_systems(Update, update_from_midi::<T>);
+    app.init_resource::<MidiSmoothing<T>>();
+
+    // Add MIDI update systems for this type, saving sees the change in the same frame
+    app.add_systems(
+        Update,
+        (update_from_midi::<T>, smooth_params::<T>)
+            .chain()
+            .before(crate::persistence_plugin::save_on_change::<T>),
+    );
+    app.add_systems(PostUpdate, send_midi_feedback::<T>);
+}
+
+/// Bevy event fired when a control mapped with `trigger` on a field of `T` is pressed
+///
+/// Triggers are one-shot actions such as resetting a scene; their fields are never written or
+/// persisted.
+///
+/// ```rust,no_run
+/// # use bevy::prelude::*;
+/// # use bevy_midi_params::prelude::*;
+/// #[derive(Resource, Default, MidiParams)]
+/// struct SceneParams {
+///     #[midi(note = 40, trigger)]
+///     pub reset: (),
+/// }
+///
+/// fn reset_scene(mut triggers: EventReader<MidiTriggered<SceneParams>>) {
+///     for trigger in triggers.read() {
+///         if trigger.field == "reset" {
+///             // Respawn everything
+///         }
+///     }
+/// }
+/// ```
+#[derive(bevy::prelude::Event)]
+pub struct MidiTriggered<T> {
+    /// Name of the trigger field
+    pub field: String,
+    /// Normalized press value (0.0-1.0), the note velocity or CC value
+    pub value: f32,
+    _params: std::marker::PhantomData<fn() -> T>,
+}
+
+impl<T> MidiTriggered<T> {
+    /// Create a trigger event, e.g. to fire a trigger from a UI button
+    pub fn new(field: impl Into<String>, value: f32) -> Self {
+        Self {
+            field: field.into(),
+            value,
+            _params: std::marker::PhantomData,
+        }
+    }
 }
 
-/// Generic system to update parameters from MIDI input
+/// Generic system to apply MIDI input received this frame to the parameters
+///
+/// Only fresh CC events are applied, so values set by persistence or the UI are left
+/// alone until the corresponding control is actually moved, and then taken over according
+/// to the mapping's `Takeover` mode. Smoothed fields get a new target to glide toward instead,
+/// and triggers fire `MidiTriggered` events.
 #[cfg(feature = "midi")]
 fn update_from_midi<T: Resource + PersistableParams>(
-    midi_controller: Res<MidiController>,
+    midi_controller: Option<Res<MidiController>>,
     mut params: ResMut<T>,
-    persistence_controller: Res<PersistenceController>,
+    mut smoothing: ResMut<MidiSmoothing<T>>,
+    mut triggers: EventWriter<MidiTriggered<T>>,
+    mut takeover: Local<HashMap<(usize, u8, String), TakeoverState>>,
 ) {
+    // The controller only exists once MidiControlPlugin has been added
+    let Some(midi_controller) = midi_controller else {
+        return;
+    };
+
+    if midi_controller.control_events().is_empty() {
+        return;
+    }
+
+    let mappings = T::get_param_mappings();
     let mut changed = false;
 
-    // Update from MIDI input
-    for mapping in T::get_param_mappings() {
-        // Only process mappings that have MIDI control enabled
-        if let Some(cc) = mapping.cc {
-            if let Some(normalized_value) = midi_controller.values.get(&cc).copied() {
-                let scaled_value = mapping.scale_value(normalized_value);
-
-                // For range controls, pass the scaled value directly
-                // For buttons, we pass the normalized value (> 0.5 triggers toggle)
-                let value_to_pass = match mapping.control_type {
-                    crate::ControlType::Range { .. } => scaled_value,
-                    crate::ControlType::Button => normalized_value,
+    for event in midi_controller.control_events() {
+        let device_name = midi_controller.device_name(event.device).unwrap_or_default();
+
+        for mapping in mappings
+            .iter()
+            .filter(|m| m.responds_to(device_name, event.channel, event.control))
+        {
+            // A gliding field is treated as being at its target already
+            let current = smoothing
+                .target(&mapping.field_name)
+                .or_else(|| params.get_midi_value(&mapping.field_name));
+
+            let value = if mapping.relative.is_some() {
+                // Encoders move the field from wherever it is
+                current.and_then(|current| mapping.relative_value(event, current))
+            } else if mapping.takeover == Takeover::Jump {
+                mapping.midi_value(event)
+            } else {
+                // Soft takeover is tracked per physical control
+                let Some(current) = current else {
+                    continue;
                 };
+                let state = takeover
+                    .entry((event.device, event.channel, mapping.field_name.clone()))
+                    .or_default();
+                mapping.takeover_value(event, current, state)
+            };
+            let Some(value) = value else {
+                continue;
+            };
 
-                if params.update_from_midi(cc, value_to_pass) {
-                    changed = true;
-                }
+            if mapping.control_type == ControlType::Trigger {
+                triggers.write(MidiTriggered::new(mapping.field_name.clone(), value));
+            } else if mapping.smoothing.is_some() {
+                smoothing.set_target(mapping.field_name.clone(), value);
+            } else if params
+                .bypass_change_detection()
+                .update_from_midi(&mapping.field_name, value)
+            {
+                changed = true;
             }
         }
     }
 
-    // Auto-save when values change via MIDI
+    // Saving is handled by save_on_change once the resource is marked changed
     if changed {
-        if let Err(e) = save_params_to_file(&persistence_controller, &*params) {
-            error!("Failed to save MIDI parameter changes: {}", e);
-        }
+        params.set_changed();
     }
 }
 
-/// Helper function to save parameters to file (MIDI version)
+/// What `send_midi_feedback` last sent for one params type
+#[cfg(feature = "midi")]
+#[derive(Default)]
+struct FeedbackState {
+    /// Messages last sent per mapping (by position), unchanged values are not sent again
+    sent: HashMap<usize, Vec<MidiMessage>>,
+    /// `MidiController::output_generation` the sent messages belong to
+    generation: u64,
+}
+
+/// Generic system to send field values back to the controls they are mapped to
+///
+/// Runs whenever the parameters change, whatever changed them (MIDI, UI, persistence or
+/// presets), so LEDs and motorized faders always show the current values. Newly opened
+/// outputs receive every value.
 #[cfg(feature = "midi")]
-fn save_params_to_file<T: PersistableParams>(
-    controller: &PersistenceController,
-    params: &T,
-) -> crate::MidiResult<()> {
-    let mut persist_file = controller.load_persist_file()?;
-    let new_data = params.to_persist_data();
+fn send_midi_feedback<T: Resource + PersistableParams>(
+    midi_controller: Option<Res<MidiController>>,
+    params: Res<T>,
+    smoothing: Res<MidiSmoothing<T>>,
+    mut state: Local<FeedbackState>,
+) {
+    let Some(midi_controller) = midi_controller else {
+        return;
+    };
+
+    let new_outputs = state.generation != midi_controller.output_generation();
+    let dirty = params.is_changed() || smoothing.is_changed() || new_outputs;
+    if !midi_controller.feedback_enabled() || !dirty {
+        return;
+    }
 
-    persist_file.set_type_data(T::get_type_name().to_string(), new_data);
-    controller.save_persist_file(&mut persist_file)?;
+    if new_outputs {
+        state.sent.clear();
+        state.generation = midi_controller.output_generation();
+    }
+
+    for (index, mapping) in T::get_param_mappings().into_iter().enumerate() {
+        // Gliding fields report where they are heading, so motorized faders don't trail behind
+        let Some(value) = smoothing
+            .target(&mapping.field_name)
+            .or_else(|| params.get_feedback_value(&mapping.field_name))
+        else {
+            continue;
+        };
 
-    Ok(())
+        let messages = mapping.feedback_messages(value);
+        if messages.is_empty() || state.sent.get(&index) == Some(&messages) {
+            continue;
+        }
+
+        midi_controller.send_feedback(&mapping, &messages);
+        state.sent.insert(index, messages);
+    }
 }
 
 // Stub implementations for when MIDI feature is disabled
@@ -195,6 +509,10 @@ impl MidiControlPlugin {
     pub fn new() -> Self { Self }
     pub fn with_controller(self, _controller_name: impl Into<String>) -> Self { self }
     pub fn no_auto_connect(self) -> Self { self }
+    pub fn with_scan_interval(self, _interval: std::time::Duration) -> Self { self }
+    pub fn with_connect_timeout(self, _timeout: std::time::Duration) -> Self { self }
+    pub fn no_hot_plug(self) -> Self { self }
+    pub fn no_feedback(self) -> Self { self }
 }
 
 #[cfg(not(feature = "midi"))]
@@ -30,6 +30,17 @@ impl PersistData {
             .get(key)
             .and_then(|v| serde_json::from_value(v.clone()).ok())
     }
+
+    /// Like `get`, but reports values that exist and fail to deserialize
+    pub fn try_get<T: serde::de::DeserializeOwned>(&self, key: &str) -> MidiResult<Option<T>> {
+        self.values
+            .get(key)
+            .map(|v| serde_json::from_value(v.clone()))
+            .transpose()
+            .map_err(|e| {
+                crate::MidiError::PersistenceError(format!("Invalid value for '{}': {}", key, e))
+            })
+    }
 }
 
 impl Default for PersistData {
@@ -68,7 +79,7 @@ impl MidiPersistFile {
         })?;
 
         // Try RON first, fallback to JSON
-        if path.extension().map_or(false, |ext| ext == "ron") {
+        if path.extension().is_some_and(|ext| ext == "ron") {
             ron::from_str(&content)
                 .map_err(|e| crate::MidiError::PersistenceError(format!("RON parse error: {}", e)))
         } else {
@@ -90,7 +101,7 @@ impl MidiPersistFile {
             })?;
         }
 
-        let content = if path.extension().map_or(false, |ext| ext == "ron") {
+        let content = if path.extension().is_some_and(|ext| ext == "ron") {
             ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|e| {
                 crate::MidiError::PersistenceError(format!("RON serialization error: {}", e))
             })?
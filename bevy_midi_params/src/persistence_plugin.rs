@@ -1,4 +1,4 @@
-use crate::{MidiResult, PersistData};
+use crate::{MidiError, MidiResult, PersistData};
 use bevy::prelude::*;
 use log::{debug, error, info, warn};
 
@@ -36,6 +36,8 @@ impl Plugin for ParamsPersistencePlugin {
 
         // Load persisted values on startup
         app.add_systems(Startup, load_all_persisted_values);
+        // Catch anything not saved yet (e.g. changes made on the last frame)
+        app.add_systems(Last, save_all_on_exit.run_if(on_event::<AppExit>));
     }
 }
 
@@ -44,25 +46,52 @@ impl Plugin for ParamsPersistencePlugin {
 pub struct ParamsRegistration {
     pub type_name: &'static str,
     pub register_fn: fn(&mut App),
+    /// Applies persisted data to the live resource
+    pub load_fn: fn(&mut World, &PersistData) -> MidiResult<()>,
+    /// Reads the live resource into persistence data (None if the resource is missing)
+    pub save_fn: fn(&World) -> Option<PersistData>,
+    /// Renders the live resource's controls, returns true if the user edited it
+    pub ui_fn: ParamsUiFn,
 }
 
+/// Signature of `ParamsRegistration::ui_fn` (takes the unit type instead of an egui `Ui` without the "ui" feature)
+#[cfg(feature = "ui")]
+pub type ParamsUiFn = fn(&mut World, &mut bevy_egui::egui::Ui) -> bool;
+
+#[cfg(not(feature = "ui"))]
+pub type ParamsUiFn = fn(&mut World, &mut ()) -> bool;
+
 inventory::collect!(ParamsRegistration);
 
 /// Trait for types that can be persisted and optionally controlled via MIDI
 pub trait PersistableParams {
-    /// Update fields from MIDI input (if MIDI feature enabled), returns true if any field changed
+    /// Update the named field from MIDI input (if MIDI feature enabled), returns true if it changed
+    #[cfg(feature = "midi")]
+    fn update_from_midi(&mut self, field_name: &str, value: f32) -> bool;
+
+    /// Current value of a MIDI-controlled field in the form `update_from_midi` accepts
+    /// (scaled for ranges, 0.0/1.0 for buttons), None for unknown or persist-only fields
     #[cfg(feature = "midi")]
-    fn update_from_midi(&mut self, cc: u8, value: f32) -> bool;
+    fn get_midi_value(&self, field_name: &str) -> Option<f32>;
+
+    /// Value sent back to the control of a field, None when the field cannot tell it apart
+    /// from other controls (yaw and roll of a rotation pitched ±90°), so the device keeps
+    /// showing what it last got
+    #[cfg(feature = "midi")]
+    fn get_feedback_value(&self, field_name: &str) -> Option<f32> {
+        self.get_midi_value(field_name)
+    }
 
     /// Get all parameter mappings for this type
     fn get_param_mappings() -> Vec<crate::MidiMapping>;
 
-    /// Render UI controls (egui or unit type if no UI)
+    /// Render UI controls (egui or unit type if no UI), `defaults` are the values the reset
+    /// buttons restore
     #[cfg(feature = "ui")]
-    fn render_ui(&mut self, ui: &mut egui::Ui) -> bool;
+    fn render_ui(&mut self, ui: &mut bevy_egui::egui::Ui, defaults: &Self) -> bool;
 
     #[cfg(not(feature = "ui"))]
-    fn render_ui(&mut self, ui: &mut ()) -> bool;
+    fn render_ui(&mut self, ui: &mut (), defaults: &Self) -> bool;
 
     /// Get type name for persistence
     fn get_type_name() -> &'static str;
@@ -70,8 +99,8 @@ pub trait PersistableParams {
     /// Convert to persistence data
     fn to_persist_data(&self) -> PersistData;
 
-    /// Load from persistence data
-    fn from_persist_data(&mut self, data: &PersistData);
+    /// Load from persistence data, returns an error naming any fields that could not be restored
+    fn apply_persist_data(&mut self, data: &PersistData) -> MidiResult<()>;
 }
 
 /// Controller for parameter persistence (lightweight, no MIDI dependencies)
@@ -124,12 +153,94 @@ pub fn register_persistable_type<T: Resource + PersistableParams + Default>(app:
         controller.register_type(type_name);
     }
 
+    // Trigger events exist even without the "midi" feature, so systems reading them still run
+    app.add_event::<crate::MidiTriggered<T>>();
+
     // Add systems for this type
     app.add_systems(Update, save_on_change::<T>);
 }
 
+/// Type-erased loader used by `ParamsRegistration::load_fn`
+pub fn load_persisted_params<T: Resource + PersistableParams>(
+    world: &mut World,
+    data: &PersistData,
+) -> MidiResult<()> {
+    let Some(mut params) = world.get_resource_mut::<T>() else {
+        return Err(MidiError::PersistenceError(format!(
+            "Resource {} is not present in the world",
+            T::get_type_name()
+        )));
+    };
+
+    params.apply_persist_data(data)
+}
+
+/// Type-erased saver used by `ParamsRegistration::save_fn`
+pub fn save_persisted_params<T: Resource + PersistableParams>(world: &World) -> Option<PersistData> {
+    world.get_resource::<T>().map(|params| params.to_persist_data())
+}
+
+/// Default values of a params type, built once for the reset buttons of its UI
+#[cfg(feature = "ui")]
+#[derive(Resource)]
+struct ParamsDefaults<T>(T);
+
+/// Type-erased renderer used by `ParamsRegistration::ui_fn`
+///
+/// The resource is only marked changed when the user actually edited it, so persistence and
+/// MIDI feedback pick up UI edits without saving on every frame.
+#[cfg(feature = "ui")]
+pub fn render_params_ui<T: Resource + PersistableParams + Default>(
+    world: &mut World,
+    ui: &mut bevy_egui::egui::Ui,
+) -> bool {
+    if !world.contains_resource::<T>() {
+        ui.weak("Not added to this app");
+        return false;
+    }
+    if !world.contains_resource::<ParamsDefaults<T>>() {
+        world.insert_resource(ParamsDefaults(T::default()));
+    }
+
+    world.resource_scope(|world, defaults: Mut<ParamsDefaults<T>>| {
+        let mut params = world.resource_mut::<T>();
+        let changed = params.bypass_change_detection().render_ui(ui, &defaults.0);
+        if changed {
+            params.set_changed();
+        }
+        changed
+    })
+}
+
+#[cfg(not(feature = "ui"))]
+pub fn render_params_ui<T: Resource + PersistableParams + Default>(_world: &mut World, _ui: &mut ()) -> bool {
+    false
+}
+
+/// Write the current value of every registered type to the persistence file
+pub fn save_all_persisted_values(world: &World) -> MidiResult<()> {
+    let controller = world.resource::<PersistenceController>();
+    let mut persist_file = controller.load_persist_file()?;
+
+    for registration in inventory::iter::<ParamsRegistration> {
+        if let Some(data) = (registration.save_fn)(world) {
+            persist_file.set_type_data(registration.type_name.to_string(), data);
+        }
+    }
+
+    controller.save_persist_file(&mut persist_file)
+}
+
 // ===== SYSTEM IMPLEMENTATIONS =====
 
+/// Save every registered type when the app exits
+fn save_all_on_exit(world: &World) {
+    match save_all_persisted_values(world) {
+        Ok(()) => info!("Saved all parameters on exit"),
+        Err(e) => error!("Failed to save parameters on exit: {}", e),
+    }
+}
+
 /// Load persisted values for all registered types on startup
 fn load_all_persisted_values(world: &mut World) {
     let persist_file = {
@@ -143,22 +254,39 @@ fn load_all_persisted_values(world: &mut World) {
         }
     };
 
-    // Load data for each registered type
+    // Load data for each registered type, reporting failures per type
     for registration in inventory::iter::<ParamsRegistration> {
-        if let Some(data) = persist_file.get_type_data(registration.type_name) {
-            info!("Loading {} from persistence", registration.type_name);
-            // The actual loading will be handled by the specific type's system
-            // This is a placeholder for the loading mechanism
+        let Some(data) = persist_file.get_type_data(registration.type_name) else {
+            continue;
+        };
+
+        match (registration.load_fn)(world, data) {
+            Ok(()) => info!("Loaded {} from persistence", registration.type_name),
+            Err(e) => error!("Failed to load {} from persistence: {}", registration.type_name, e),
         }
     }
 }
 
 /// Save parameters when they change (UI or other modifications)
-fn save_on_change<T: Resource + PersistableParams>(
+///
+/// Smoothed fields change on every frame of a glide, so changes are saved once every glide has
+/// arrived.
+pub(crate) fn save_on_change<T: Resource + PersistableParams>(
     controller: Res<PersistenceController>,
     params: Res<T>,
+    #[cfg(feature = "midi")] smoothing: Option<Res<crate::MidiSmoothing<T>>>,
+    mut unsaved: Local<bool>,
 ) {
     if params.is_changed() && !params.is_added() {
+        *unsaved = true;
+    }
+
+    #[cfg(feature = "midi")]
+    if smoothing.is_some_and(|smoothing| smoothing.is_gliding()) {
+        return;
+    }
+
+    if std::mem::take(&mut *unsaved) {
         if let Err(e) = save_params_to_file(&controller, &*params) {
             error!("Failed to save parameter changes: {}", e);
         } else {
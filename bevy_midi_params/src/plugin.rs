@@ -1,13 +1,11 @@
-use crate::{MidiResult, PersistData};
-#[cfg(feature = "midi")]
-use crate::MidiController;
+use crate::{MidiControlPlugin, MidiResult, ParamsPersistencePlugin, PersistData};
 use bevy::prelude::*;
-use log::{debug, error, info, warn};
 
 /// Main plugin for MIDI parameter integration
 /// 
 /// **DEPRECATED**: Use `ParamsPersistencePlugin` and `MidiControlPlugin` instead.
 /// For convenience, use `bevy_midi_params::dev_plugins()` or `bevy_midi_params::prod_plugins()`.
+/// It adds both with its settings, so apps still using it keep working.
 #[deprecated(since = "0.2.0", note = "Use ParamsPersistencePlugin and MidiControlPlugin instead")]
 pub struct MidiParamsPlugin {
     /// Path to persistence file
@@ -18,6 +16,8 @@ pub struct MidiParamsPlugin {
     pub preferred_controller: Option<String>,
 }
 
+// The plugin's own impls use its deprecated fields
+#[allow(deprecated)]
 impl Default for MidiParamsPlugin {
     fn default() -> Self {
         Self {
@@ -28,6 +28,7 @@ impl Default for MidiParamsPlugin {
     }
 }
 
+#[allow(deprecated)]
 impl MidiParamsPlugin {
     /// Create new plugin with default settings
     pub fn new() -> Self {
@@ -53,51 +54,36 @@ impl MidiParamsPlugin {
     }
 }
 
+#[allow(deprecated)]
 impl Plugin for MidiParamsPlugin {
     fn build(&self, app: &mut App) {
-        // Insert MIDI controller resource (only if MIDI feature is enabled)
-        #[cfg(feature = "midi")]
-        {
-            app.insert_resource(MidiController::new(
-                self.persist_file.clone(),
-                self.preferred_controller.clone(),
-            ));
-
-            if self.auto_connect {
-                app.add_systems(Startup, setup_midi_input);
-            }
-
-            app.add_systems(PreUpdate, update_midi_controller);
+        // Same as adding the plugins that replaced it with these settings
+        app.add_plugins(ParamsPersistencePlugin {
+            persist_file: self.persist_file.clone(),
+        });
+
+        let mut midi_control = MidiControlPlugin::new();
+        if let Some(controller_name) = &self.preferred_controller {
+            midi_control = midi_control.with_controller(controller_name.clone());
         }
-
-        // Auto-register all MidiParams types that have been defined
-        for registration in inventory::iter::<MidiParamsRegistration> {
-            info!("Auto-registering MIDI type: {}", registration.type_name);
-            (registration.register_fn)(app);
+        if !self.auto_connect {
+            midi_control = midi_control.no_auto_connect();
         }
+        app.add_plugins(midi_control);
     }
 }
 
-/// Registration data for auto-discovered MidiParams types
-#[derive(Debug)]
-pub struct MidiParamsRegistration {
-    pub type_name: &'static str,
-    pub register_fn: fn(&mut App),
-}
-
-inventory::collect!(MidiParamsRegistration);
-
 /// Trait for types that can be controlled via MIDI
 pub trait MidiControllable {
-    /// Update fields from MIDI input, returns true if any field changed
-    fn update_from_midi(&mut self, cc: u8, value: f32) -> bool;
+    /// Update the named field from MIDI input, returns true if it changed
+    fn update_from_midi(&mut self, field_name: &str, value: f32) -> bool;
 
     /// Get all MIDI mappings for this type
     fn get_midi_mappings() -> Vec<crate::MidiMapping>;
 
     /// Render UI controls (egui or unit type if no UI)
     #[cfg(feature = "ui")]
-    fn render_ui(&mut self, ui: &mut egui::Ui) -> bool;
+    fn render_ui(&mut self, ui: &mut bevy_egui::egui::Ui) -> bool;
 
     #[cfg(not(feature = "ui"))]
     fn render_ui(&mut self, ui: &mut ()) -> bool;
@@ -108,143 +94,6 @@ pub trait MidiControllable {
     /// Convert to persistence data
     fn to_persist_data(&self) -> PersistData;
 
-    /// Load from persistence data
-    fn from_persist_data(&mut self, data: &PersistData);
-}
-
-/// Register a MidiParams type with the controller
-pub fn register_midi_type<T: Resource + MidiControllable + Default>(app: &mut App) {
-    let type_name = T::get_type_name();
-
-    let world = app.world_mut();
-
-    // Ensure resource exists
-    if !world.contains_resource::<T>() {
-        world.init_resource::<T>();
-    }
-
-    // Register mappings with the controller (only if MIDI feature is enabled)
-    #[cfg(feature = "midi")]
-    {
-        if let Some(mut midi_controller) = world.get_resource_mut::<MidiController>() {
-            for mapping in T::get_midi_mappings() {
-                midi_controller.register_mapping(mapping);
-            }
-            midi_controller.register_type(type_name);
-        }
-
-        // Add systems for this type
-        app.add_systems(
-            Update,
-            (update_and_persist_params::<T>, save_on_ui_change::<T>),
-        );
-    }
-}
-
-// ===== SYSTEM IMPLEMENTATIONS =====
-
-/// Setup MIDI input connection
-#[cfg(feature = "midi")]
-fn setup_midi_input(mut midi_controller: ResMut<MidiController>) {
-    match midi_controller.connect_midi() {
-        Ok(()) => info!("MIDI connection established"),
-        Err(e) => warn!("Failed to connect MIDI: {}", e),
-    }
-}
-
-#[cfg(feature = "midi")]
-fn update_midi_controller(mut midi_controller: ResMut<MidiController>) {
-    midi_controller.update_values();
-}
-
-/// Load persisted values for all registered types
-#[cfg(feature = "midi")]
-fn load_all_persisted_values(world: &mut World) {
-    let persist_file = {
-        let midi_controller = world.resource::<MidiController>();
-        match midi_controller.load_persist_file() {
-            Ok(file) => file,
-            Err(e) => {
-                warn!("Failed to load persistence file: {}", e);
-                return;
-            }
-        }
-    };
-
-    // Load data for each registered type
-    for registration in inventory::iter::<MidiParamsRegistration> {
-        if let Some(data) = persist_file.get_type_data(registration.type_name) {
-            // This is a bit tricky - we need to call from_persist_data on the right resource
-            // For now, we'll do a runtime dispatch. In a real implementation,
-            // this could be improved with a trait object or type registry.
-            info!("Would load {} from persistence", registration.type_name);
-        }
-    }
-}
-
-/// Generic system to update parameters from MIDI and auto-save changes
-#[cfg(feature = "midi")]
-fn update_and_persist_params<T: Resource + MidiControllable>(
-    midi_controller: Res<MidiController>,
-    mut params: ResMut<T>,
-) {
-    let mut changed = false;
-
-    // Update from MIDI input
-    for mapping in T::get_midi_mappings() {
-        // Only process mappings that have MIDI control enabled
-        if let Some(cc) = mapping.cc {
-            if let Some(normalized_value) = midi_controller.values.get(&cc).copied() {
-                let scaled_value = mapping.scale_value(normalized_value);
-
-                // For range controls, pass the scaled value directly
-                // For buttons, we pass the normalized value (> 0.5 triggers toggle)
-                let value_to_pass = match mapping.control_type {
-                    crate::ControlType::Range { .. } => scaled_value,
-                    crate::ControlType::Button => normalized_value,
-                };
-
-                if params.update_from_midi(cc, value_to_pass) {
-                    changed = true;
-                }
-            }
-        }
-    }
-
-    // Auto-save when values change via MIDI
-    if changed {
-        if let Err(e) = save_params_to_file(&midi_controller, &*params) {
-            error!("Failed to save MIDI parameters: {}", e);
-        }
-    }
-}
-
-/// Save parameters when UI changes them
-#[cfg(feature = "midi")]
-fn save_on_ui_change<T: Resource + MidiControllable>(
-    midi_controller: Res<MidiController>,
-    params: Res<T>,
-) {
-    if params.is_changed() && !params.is_added() {
-        if let Err(e) = save_params_to_file(&midi_controller, &*params) {
-            error!("Failed to save UI parameter changes: {}", e);
-        } else {
-            debug!("Auto-saved {} changes", T::get_type_name());
-        }
-    }
-}
-
-/// Helper function to save parameters to file
-#[cfg(feature = "midi")]
-fn save_params_to_file<T: MidiControllable>(
-    midi_controller: &MidiController,
-    params: &T,
-) -> MidiResult<()> {
-    let mut persist_file = midi_controller.load_persist_file()?;
-    let new_data = params.to_persist_data();
-
-    persist_file.set_type_data(T::get_type_name().to_string(), new_data);
-    midi_controller.save_persist_file(&mut persist_file)?;
-
-    Ok(())
+    /// Load from persistence data, returns an error naming any fields that could not be restored
+    fn apply_persist_data(&mut self, data: &PersistData) -> MidiResult<()>;
 }
@@ -0,0 +1,241 @@
+use crate::source::ChannelConnector;
+use crate::{MidiConnector, MidiError, MidiMessage, MidiResult, OpenedPort};
+use log::warn;
+use midir::{MidiOutput, MidiOutputConnection};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+/// A MIDI message together with the output port it was sent to
+#[derive(Debug, Clone, PartialEq)]
+pub struct SentMessage {
+    /// Name of the output port
+    pub port: String,
+    pub message: MidiMessage,
+}
+
+/// A transport that sends MIDI messages back to devices, e.g. to light LEDs or move
+/// motorized faders
+///
+/// `MidirSink` talks to real hardware; `ChannelSink` hands everything sent to a
+/// `ChannelSinkReceiver`, which is useful for tests.
+pub trait MidiSink: Send + 'static {
+    /// New connector listing and opening output ports for this sink
+    ///
+    /// Each connector has its own backend client, so a stalled scan never holds up `send`.
+    fn connector(&self) -> Box<dyn MidiConnector>;
+
+    /// Start sending to a port opened by one of this sink's connectors
+    fn attach(&mut self, port: OpenedPort);
+
+    /// Close the named output port, e.g. after the device has been unplugged
+    fn disconnect(&mut self, port_name: &str);
+
+    /// Send a message to an open output port
+    fn send(&mut self, port_name: &str, message: &MidiMessage) -> MidiResult<()>;
+}
+
+/// MIDI output to hardware through midir
+pub struct MidirSink {
+    /// Client name reported to the OS MIDI system
+    client_name: String,
+    /// Open connections by port name
+    connections: Vec<(String, MidiOutputConnection)>,
+}
+
+impl MidirSink {
+    pub fn new(client_name: impl Into<String>) -> Self {
+        Self {
+            client_name: client_name.into(),
+            connections: Vec::new(),
+        }
+    }
+}
+
+impl Default for MidirSink {
+    fn default() -> Self {
+        Self::new("bevy_midi_params")
+    }
+}
+
+impl MidiSink for MidirSink {
+    fn connector(&self) -> Box<dyn MidiConnector> {
+        Box::new(MidirOutputConnector {
+            client_name: self.client_name.clone(),
+            client: None,
+        })
+    }
+
+    fn attach(&mut self, port: OpenedPort) {
+        match port.connection.downcast::<MidiOutputConnection>() {
+            Ok(connection) => {
+                self.disconnect(&port.name);
+                self.connections.push((port.name, *connection));
+            }
+            Err(_) => warn!("Ignoring MIDI output {} opened by another transport", port.name),
+        }
+    }
+
+    fn disconnect(&mut self, port_name: &str) {
+        if let Some(index) = self.connections.iter().position(|(name, _)| name == port_name) {
+            let (_, connection) = self.connections.remove(index);
+            connection.close();
+        }
+    }
+
+    fn send(&mut self, port_name: &str, message: &MidiMessage) -> MidiResult<()> {
+        let (_, connection) = self
+            .connections
+            .iter_mut()
+            .find(|(name, _)| name == port_name)
+            .ok_or_else(|| {
+                MidiError::ConnectionFailed(format!("MIDI output port '{}' is not open", port_name))
+            })?;
+
+        connection
+            .send(&message.to_bytes())
+            .map_err(|e| MidiError::ConnectionFailed(format!("Failed to send MIDI: {}", e)))
+    }
+}
+
+/// Opens midir output ports for a `MidirSink`
+struct MidirOutputConnector {
+    /// Client name reported to the OS MIDI system
+    client_name: String,
+    /// Client reused by every scan, opening a connection consumes it
+    client: Option<MidiOutput>,
+}
+
+impl MidirOutputConnector {
+    /// Take the cached client, creating one if the last was used up by a connection
+    fn take_client(&mut self) -> MidiResult<MidiOutput> {
+        if let Some(midi_out) = self.client.take() {
+            return Ok(midi_out);
+        }
+
+        MidiOutput::new(&self.client_name).map_err(|e| {
+            MidiError::ConnectionFailed(format!("Failed to create MIDI output: {}", e))
+        })
+    }
+}
+
+impl MidiConnector for MidirOutputConnector {
+    fn available_ports(&mut self) -> MidiResult<Vec<String>> {
+        let midi_out = self.take_client()?;
+        let ports = midi_out
+            .ports()
+            .iter()
+            .filter_map(|port| midi_out.port_name(port).ok())
+            .collect();
+        self.client = Some(midi_out);
+        Ok(ports)
+    }
+
+    fn connect(&mut self, port_name: &str) -> MidiResult<OpenedPort> {
+        let midi_out = self.take_client()?;
+
+        let Some(out_port) = midi_out
+            .ports()
+            .into_iter()
+            .find(|port| midi_out.port_name(port).is_ok_and(|name| name == port_name))
+        else {
+            self.client = Some(midi_out);
+            return Err(MidiError::ConnectionFailed(format!(
+                "MIDI output port '{}' not found",
+                port_name
+            )));
+        };
+
+        let connection = midi_out
+            .connect(&out_port, "bevy-midi-params")
+            .map_err(|e| {
+                let message = format!("Connection failed: {}", e);
+                // A failed connection hands the client back
+                self.client = Some(e.into_inner());
+                MidiError::ConnectionFailed(message)
+            })?;
+
+        Ok(OpenedPort::new(port_name, connection))
+    }
+}
+
+/// In-memory MIDI output, everything sent to a connected port ends up in a `ChannelSinkReceiver`
+pub struct ChannelSink {
+    /// Port names reported by the connector
+    ports: Arc<Mutex<Vec<String>>>,
+    /// Currently connected ports
+    connected: Vec<String>,
+    sender: Sender<SentMessage>,
+}
+
+/// Receiving half of a `ChannelSink`, cheap to clone and usable from any thread
+#[derive(Clone)]
+pub struct ChannelSinkReceiver {
+    receiver: Arc<Mutex<Receiver<SentMessage>>>,
+}
+
+impl ChannelSink {
+    /// Create a sink with a single port named "In-memory"
+    pub fn new() -> (Self, ChannelSinkReceiver) {
+        Self::with_ports(["In-memory"])
+    }
+
+    /// Create a sink that reports the given port names
+    pub fn with_ports(
+        ports: impl IntoIterator<Item = impl Into<String>>,
+    ) -> (Self, ChannelSinkReceiver) {
+        let (sender, receiver) = mpsc::channel();
+        let sink = Self {
+            ports: Arc::new(Mutex::new(ports.into_iter().map(Into::into).collect())),
+            connected: Vec::new(),
+            sender,
+        };
+        let receiver = ChannelSinkReceiver {
+            receiver: Arc::new(Mutex::new(receiver)),
+        };
+        (sink, receiver)
+    }
+}
+
+impl MidiSink for ChannelSink {
+    fn connector(&self) -> Box<dyn MidiConnector> {
+        Box::new(ChannelConnector {
+            ports: self.ports.clone(),
+        })
+    }
+
+    fn attach(&mut self, port: OpenedPort) {
+        if !self.connected.contains(&port.name) {
+            self.connected.push(port.name);
+        }
+    }
+
+    fn disconnect(&mut self, port_name: &str) {
+        self.connected.retain(|port| port != port_name);
+    }
+
+    fn send(&mut self, port_name: &str, message: &MidiMessage) -> MidiResult<()> {
+        if !self.connected.iter().any(|port| port == port_name) {
+            return Err(MidiError::ConnectionFailed(format!(
+                "MIDI output port '{}' is not open",
+                port_name
+            )));
+        }
+
+        // A dropped receiver just means nobody is listening
+        let _ = self.sender.send(SentMessage {
+            port: port_name.to_string(),
+            message: message.clone(),
+        });
+        Ok(())
+    }
+}
+
+impl ChannelSinkReceiver {
+    /// Take every message sent since the previous call, in sending order
+    pub fn take(&self) -> Vec<SentMessage> {
+        self.receiver
+            .lock()
+            .map(|receiver| receiver.try_iter().collect())
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,147 @@
+use crate::PersistableParams;
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+/// A smoothed field on its way to a target
+#[derive(Debug, Clone, Copy)]
+struct Glide {
+    target: f32,
+    /// Value written by the previous step, to notice changes made elsewhere
+    written: Option<f32>,
+}
+
+/// Targets that smoothed fields of `T` are gliding toward
+///
+/// MIDI input to a field mapped with `smooth` sets its target here, and the field itself moves
+/// toward it a little every frame. The field holds the current value; `target` tells where it
+/// is heading. Changing the field from anywhere else (UI, presets) cancels its glide.
+#[derive(Resource)]
+pub struct MidiSmoothing<T> {
+    glides: HashMap<String, Glide>,
+    _params: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for MidiSmoothing<T> {
+    fn default() -> Self {
+        Self {
+            glides: HashMap::new(),
+            _params: PhantomData,
+        }
+    }
+}
+
+impl<T> MidiSmoothing<T> {
+    /// Value a field is gliding toward, None once it has arrived
+    pub fn target(&self, field_name: &str) -> Option<f32> {
+        self.glides.get(field_name).map(|glide| glide.target)
+    }
+
+    /// Whether any field is still gliding
+    pub fn is_gliding(&self) -> bool {
+        !self.glides.is_empty()
+    }
+
+    /// Fields that are still gliding, with their targets
+    pub fn targets(&self) -> impl Iterator<Item = (&str, f32)> {
+        self.glides
+            .iter()
+            .map(|(field_name, glide)| (field_name.as_str(), glide.target))
+    }
+
+    /// Make a smoothed field glide toward a value, as if a control had been moved there
+    ///
+    /// Targets for fields mapped without `smooth` are dropped on the next frame.
+    pub fn set_target(&mut self, field_name: impl Into<String>, target: f32) {
+        self.glides
+            .entry(field_name.into())
+            .and_modify(|glide| glide.target = target)
+            .or_insert(Glide {
+                target,
+                written: None,
+            });
+    }
+}
+
+/// Move every smoothed field of `T` toward its target
+///
+/// A field covers 95% of the way in its smoothing time, then snaps to the target. Without a
+/// `Time` resource fields jump straight to their targets.
+pub(crate) fn smooth_params<T: Resource + PersistableParams>(
+    time: Option<Res<Time>>,
+    mut smoothing: ResMut<MidiSmoothing<T>>,
+    mut params: ResMut<T>,
+) {
+    if smoothing.glides.is_empty() {
+        return;
+    }
+
+    let mappings = T::get_param_mappings();
+    smoothing.glides.retain(|field_name, _| {
+        mappings
+            .iter()
+            .any(|mapping| mapping.smoothing.is_some() && mapping.field_name == *field_name)
+    });
+
+    let delta = time.map_or(f32::INFINITY, |time| time.delta_secs());
+    let mut changed = false;
+
+    for mapping in mappings {
+        let Some(seconds) = mapping.smoothing else {
+            continue;
+        };
+        let Some(glide) = smoothing.glides.get(&mapping.field_name).copied() else {
+            continue;
+        };
+        let Some(current) = params.get_midi_value(&mapping.field_name) else {
+            smoothing.glides.remove(&mapping.field_name);
+            continue;
+        };
+
+        let span = (mapping.max_value - mapping.min_value).abs();
+        if glide
+            .written
+            .is_some_and(|written| (written - current).abs() > f32::EPSILON.max(span * 1e-6))
+        {
+            smoothing.glides.remove(&mapping.field_name);
+            continue;
+        }
+
+        let progress = if seconds > 0.0 {
+            1.0 - (-3.0 * delta / seconds).exp()
+        } else {
+            1.0
+        };
+        let mut next = current + (glide.target - current) * progress;
+        // Stepped fields would round back to `current`, they move at least one step per frame
+        if mapping.step > 0.0 && (next - current).abs() < mapping.step {
+            next = if (glide.target - current).abs() <= mapping.step {
+                glide.target
+            } else {
+                current + mapping.step.copysign(glide.target - current)
+            };
+        }
+        let arrived = (glide.target - next).abs() <= span * 1e-4;
+        if arrived {
+            next = glide.target;
+        }
+
+        if params
+            .bypass_change_detection()
+            .update_from_midi(&mapping.field_name, next)
+        {
+            changed = true;
+        }
+
+        if arrived {
+            smoothing.glides.remove(&mapping.field_name);
+        } else if let Some(glide) = smoothing.glides.get_mut(&mapping.field_name) {
+            glide.written = params.get_midi_value(&mapping.field_name);
+        }
+    }
+
+    // Saving and feedback see the step like any other change
+    if changed {
+        params.set_changed();
+    }
+}
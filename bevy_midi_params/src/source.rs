@@ -0,0 +1,381 @@
+use crate::{MidiConnector, MidiError, MidiMessage, MidiResult, OpenedPort};
+use log::{debug, warn};
+use midir::{Ignore, MidiInput, MidiInputConnection};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+/// A MIDI message together with the input port it arrived on
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceivedMessage {
+    /// Name of the input port
+    pub port: String,
+    pub message: MidiMessage,
+}
+
+/// A transport that delivers MIDI messages to the controller
+///
+/// `MidirSource` talks to real hardware; `ChannelSource` is fed from code, which is
+/// useful for tests and for bridging other transports (network, files, ...).
+pub trait MidiSource: Send + 'static {
+    /// New connector listing and opening input ports for this source
+    ///
+    /// Each connector has its own backend client, so a stalled scan never holds up `poll`.
+    fn connector(&self) -> Box<dyn MidiConnector>;
+
+    /// Start delivering messages from a port opened by one of this source's connectors
+    fn attach(&mut self, port: OpenedPort);
+
+    /// Close the named input port, e.g. after the device has been unplugged
+    fn disconnect(&mut self, port_name: &str);
+
+    /// Move all messages received since the previous poll into `messages`
+    fn poll(&mut self, messages: &mut Vec<ReceivedMessage>);
+
+    /// Whether the connector may block on an OS backend
+    ///
+    /// Such sources are refreshed on a background thread, others in place.
+    fn connects_in_background(&self) -> bool {
+        true
+    }
+}
+
+/// MIDI input from hardware through midir
+pub struct MidirSource {
+    /// Client name reported to the OS MIDI system
+    client_name: String,
+    /// Open connections (kept alive) by port name
+    connections: Vec<(String, MidiInputConnection<()>)>,
+    /// Queue of decoded messages pushed by the connection callbacks
+    pending_messages: Arc<Mutex<Vec<ReceivedMessage>>>,
+}
+
+impl MidirSource {
+    pub fn new(client_name: impl Into<String>) -> Self {
+        Self {
+            client_name: client_name.into(),
+            connections: Vec::new(),
+            pending_messages: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+}
+
+impl Default for MidirSource {
+    fn default() -> Self {
+        Self::new("bevy_midi_params")
+    }
+}
+
+impl MidiSource for MidirSource {
+    fn connector(&self) -> Box<dyn MidiConnector> {
+        Box::new(MidirInputConnector {
+            client_name: self.client_name.clone(),
+            client: None,
+            pending_messages: self.pending_messages.clone(),
+        })
+    }
+
+    fn attach(&mut self, port: OpenedPort) {
+        match port.connection.downcast::<MidiInputConnection<()>>() {
+            Ok(connection) => {
+                self.disconnect(&port.name);
+                self.connections.push((port.name, *connection));
+            }
+            Err(_) => warn!("Ignoring MIDI port {} opened by another transport", port.name),
+        }
+    }
+
+    fn disconnect(&mut self, port_name: &str) {
+        if let Some(index) = self.connections.iter().position(|(name, _)| name == port_name) {
+            let (_, connection) = self.connections.remove(index);
+            connection.close();
+        }
+    }
+
+    fn poll(&mut self, messages: &mut Vec<ReceivedMessage>) {
+        if let Ok(mut pending_lock) = self.pending_messages.lock() {
+            // Move all messages out instead of cloning
+            messages.append(&mut pending_lock);
+        }
+    }
+}
+
+/// Opens midir input ports for a `MidirSource`
+struct MidirInputConnector {
+    /// Client name reported to the OS MIDI system
+    client_name: String,
+    /// Client reused by every scan, opening a connection consumes it
+    client: Option<MidiInput>,
+    /// Queue of the source the opened ports deliver to
+    pending_messages: Arc<Mutex<Vec<ReceivedMessage>>>,
+}
+
+impl MidirInputConnector {
+    /// Take the cached client, creating one if the last was used up by a connection
+    fn take_client(&mut self) -> MidiResult<MidiInput> {
+        if let Some(midi_in) = self.client.take() {
+            return Ok(midi_in);
+        }
+
+        let mut midi_in = MidiInput::new(&self.client_name).map_err(|e| {
+            MidiError::ConnectionFailed(format!("Failed to create MIDI input: {}", e))
+        })?;
+        midi_in.ignore(Ignore::None);
+        Ok(midi_in)
+    }
+}
+
+impl MidiConnector for MidirInputConnector {
+    fn available_ports(&mut self) -> MidiResult<Vec<String>> {
+        let midi_in = self.take_client()?;
+        let ports = midi_in
+            .ports()
+            .iter()
+            .filter_map(|port| midi_in.port_name(port).ok())
+            .collect();
+        self.client = Some(midi_in);
+        Ok(ports)
+    }
+
+    fn connect(&mut self, port_name: &str) -> MidiResult<OpenedPort> {
+        let midi_in = self.take_client()?;
+
+        let Some(in_port) = midi_in
+            .ports()
+            .into_iter()
+            .find(|port| midi_in.port_name(port).is_ok_and(|name| name == port_name))
+        else {
+            self.client = Some(midi_in);
+            return Err(MidiError::ConnectionFailed(format!(
+                "MIDI port '{}' not found",
+                port_name
+            )));
+        };
+
+        let pending_messages = self.pending_messages.clone();
+        let port = port_name.to_string();
+
+        let connection = midi_in
+            .connect(
+                &in_port,
+                "bevy-midi-params",
+                move |_stamp, bytes, _| {
+                    let Some(message) = MidiMessage::parse(bytes) else {
+                        debug!("Ignoring unrecognised MIDI data: {:02X?}", bytes);
+                        return;
+                    };
+
+                    debug!("MIDI {} {:?}", port, message);
+
+                    if let Ok(mut messages) = pending_messages.lock() {
+                        messages.push(ReceivedMessage {
+                            port: port.clone(),
+                            message,
+                        });
+                    }
+                },
+                (),
+            )
+            .map_err(|e| {
+                let message = format!("Connection failed: {}", e);
+                // A failed connection hands the client back
+                self.client = Some(e.into_inner());
+                MidiError::ConnectionFailed(message)
+            })?;
+
+        Ok(OpenedPort::new(port_name, connection))
+    }
+}
+
+/// In-memory MIDI source fed through a `ChannelSourceSender`
+///
+/// Messages sent to a port that is not connected are dropped, like unplugged hardware.
+/// Ports can be plugged and unplugged through the sender to simulate hot-plugging.
+pub struct ChannelSource {
+    /// Port names reported by the connector, shared with the sender
+    ports: Arc<Mutex<Vec<String>>>,
+    /// Currently connected ports
+    connected: Vec<String>,
+    receiver: Receiver<ReceivedMessage>,
+}
+
+/// Sending half of a `ChannelSource`, cheap to clone and usable from any thread
+#[derive(Clone)]
+pub struct ChannelSourceSender {
+    /// Port used by `send` and `send_bytes`
+    default_port: String,
+    /// Port names reported by the source
+    ports: Arc<Mutex<Vec<String>>>,
+    sender: Sender<ReceivedMessage>,
+}
+
+impl ChannelSource {
+    /// Create a source with a single port named "In-memory"
+    pub fn new() -> (Self, ChannelSourceSender) {
+        Self::with_ports(["In-memory"])
+    }
+
+    /// Create a source that reports the given port names, the first one is the sender's default
+    pub fn with_ports(
+        ports: impl IntoIterator<Item = impl Into<String>>,
+    ) -> (Self, ChannelSourceSender) {
+        let (sender, receiver) = mpsc::channel();
+        let ports: Vec<String> = ports.into_iter().map(Into::into).collect();
+        let default_port = ports.first().cloned().unwrap_or_default();
+        let ports = Arc::new(Mutex::new(ports));
+
+        let source = Self {
+            ports: ports.clone(),
+            connected: Vec::new(),
+            receiver,
+        };
+        let sender = ChannelSourceSender {
+            default_port,
+            ports,
+            sender,
+        };
+        (source, sender)
+    }
+}
+
+impl MidiSource for ChannelSource {
+    fn connector(&self) -> Box<dyn MidiConnector> {
+        Box::new(ChannelConnector {
+            ports: self.ports.clone(),
+        })
+    }
+
+    fn attach(&mut self, port: OpenedPort) {
+        if !self.connected.contains(&port.name) {
+            self.connected.push(port.name);
+        }
+    }
+
+    fn disconnect(&mut self, port_name: &str) {
+        self.connected.retain(|port| port != port_name);
+    }
+
+    fn connects_in_background(&self) -> bool {
+        false
+    }
+
+    fn poll(&mut self, messages: &mut Vec<ReceivedMessage>) {
+        let connected = &self.connected;
+        messages.extend(
+            self.receiver
+                .try_iter()
+                .filter(|received| connected.contains(&received.port)),
+        );
+    }
+}
+
+/// Opens in-memory ports, for `ChannelSource` and `ChannelSink`
+pub(crate) struct ChannelConnector {
+    /// Port names that can be opened
+    pub(crate) ports: Arc<Mutex<Vec<String>>>,
+}
+
+impl MidiConnector for ChannelConnector {
+    fn available_ports(&mut self) -> MidiResult<Vec<String>> {
+        Ok(self.ports.lock().map(|ports| ports.clone()).unwrap_or_default())
+    }
+
+    fn connect(&mut self, port_name: &str) -> MidiResult<OpenedPort> {
+        if !self.available_ports()?.iter().any(|port| port == port_name) {
+            return Err(MidiError::ConnectionFailed(format!(
+                "MIDI port '{}' not found",
+                port_name
+            )));
+        }
+        Ok(OpenedPort::new(port_name, ()))
+    }
+}
+
+impl ChannelSourceSender {
+    /// Queue a decoded message on the default port, returns false if the source has been dropped
+    pub fn send(&self, message: MidiMessage) -> bool {
+        self.send_from(&self.default_port, message)
+    }
+
+    /// Queue a decoded message on the named port, returns false if the port is unplugged or the
+    /// source has been dropped
+    pub fn send_from(&self, port: &str, message: MidiMessage) -> bool {
+        if !self.is_plugged(port) {
+            return false;
+        }
+
+        self.sender
+            .send(ReceivedMessage {
+                port: port.to_string(),
+                message,
+            })
+            .is_ok()
+    }
+
+    /// Decode and queue raw MIDI bytes, returns false if they are malformed or the source is gone
+    pub fn send_bytes(&self, bytes: &[u8]) -> bool {
+        MidiMessage::parse(bytes).is_some_and(|message| self.send(message))
+    }
+
+    /// Make a port available, as if a device had been plugged in
+    pub fn plug(&self, port: impl Into<String>) {
+        let port = port.into();
+        if let Ok(mut ports) = self.ports.lock() {
+            if !ports.contains(&port) {
+                ports.push(port);
+            }
+        }
+    }
+
+    /// Remove a port, as if its device had been unplugged
+    pub fn unplug(&self, port: &str) {
+        if let Ok(mut ports) = self.ports.lock() {
+            ports.retain(|name| name != port);
+        }
+    }
+
+    /// Check whether a port is currently available
+    pub fn is_plugged(&self, port: &str) -> bool {
+        self.ports
+            .lock()
+            .is_ok_and(|ports| ports.iter().any(|name| name == port))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cc(value: u8) -> MidiMessage {
+        MidiMessage::ControlChange {
+            channel: 0,
+            control: 7,
+            value,
+        }
+    }
+
+    #[test]
+    fn channel_source_only_delivers_from_connected_ports() {
+        let (mut source, sender) = ChannelSource::with_ports(["Pads", "Faders"]);
+        let mut messages = Vec::new();
+
+        assert!(sender.send_bytes(&[0xB0, 7, 64]));
+        source.poll(&mut messages);
+        assert!(messages.is_empty());
+
+        let mut connector = source.connector();
+        assert!(connector.connect("Keys").is_err());
+        source.attach(connector.connect("Faders").unwrap());
+
+        assert!(sender.send(cc(1)));
+        assert!(sender.send_from("Faders", cc(100)));
+        assert!(!sender.send_bytes(&[0xB0, 7]));
+        source.poll(&mut messages);
+        assert_eq!(
+            messages,
+            vec![ReceivedMessage {
+                port: "Faders".to_string(),
+                message: cc(100)
+            }]
+        );
+    }
+}
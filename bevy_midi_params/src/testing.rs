@@ -0,0 +1,183 @@
+//! Helpers for driving MIDI input from tests without hardware
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_midi_params::prelude::*;
+//! use bevy_midi_params::testing::{FakeMidiPlugin, MidiTestExt};
+//!
+//! let mut app = App::new();
+//! app.add_plugins((ParamsPersistencePlugin::default(), FakeMidiPlugin::default()));
+//! app.update();
+//!
+//! // Runs a full frame with CC 7 at its maximum on channel 0
+//! app.send_cc(0, 7, 127);
+//! ```
+
+use crate::{
+    ChannelSink, ChannelSinkReceiver, ChannelSource, ChannelSourceSender, MidiControlPlugin,
+    MidiMessage, SentMessage,
+};
+use bevy::prelude::*;
+use std::time::Duration;
+
+/// Port name reported by the default fake controller
+pub const FAKE_PORT_NAME: &str = "Fake MIDI Controller";
+
+/// Resource holding both ends of the fake controller
+///
+/// Messages sent here are picked up by the real `MidiControlPlugin` systems on the next update,
+/// and feedback sent to the fake devices can be read back with `take_feedback`.
+#[derive(Resource, Clone)]
+pub struct FakeMidiController {
+    sender: ChannelSourceSender,
+    feedback: ChannelSinkReceiver,
+}
+
+impl FakeMidiController {
+    /// Queue a message from the first fake device for the next update
+    pub fn send(&self, message: MidiMessage) {
+        self.sender.send(message);
+    }
+
+    /// Queue a message from the named fake device for the next update
+    pub fn send_from(&self, device: &str, message: MidiMessage) {
+        self.sender.send_from(device, message);
+    }
+
+    /// Queue raw MIDI bytes for the next update
+    pub fn send_bytes(&self, bytes: &[u8]) {
+        self.sender.send_bytes(bytes);
+    }
+
+    /// Plug a fake device (back) in, it is connected on the next update
+    pub fn plug(&self, device: impl Into<String>) {
+        self.sender.plug(device);
+    }
+
+    /// Unplug a fake device, it is disconnected on the next update
+    pub fn unplug(&self, device: &str) {
+        self.sender.unplug(device);
+    }
+
+    /// Take the feedback sent to the fake devices since the previous call
+    ///
+    /// Only the devices given to `FakeMidiPlugin` have outputs, devices plugged in later don't.
+    pub fn take_feedback(&self) -> Vec<SentMessage> {
+        self.feedback.take()
+    }
+}
+
+/// Adds `MidiControlPlugin` backed by in-memory ports and inserts `FakeMidiController`
+///
+/// Every fake device is connected during the first update, so messages can already be sent
+/// before it. Devices are rescanned every frame, so plugging and unplugging take effect on the
+/// next update.
+pub struct FakeMidiPlugin {
+    /// Port names of the fake devices, the first one is used by `send` and the `MidiTestExt` helpers
+    pub devices: Vec<String>,
+}
+
+impl Default for FakeMidiPlugin {
+    fn default() -> Self {
+        Self::with_devices([FAKE_PORT_NAME])
+    }
+}
+
+impl FakeMidiPlugin {
+    /// Simulate several devices connected at the same time
+    pub fn with_devices(devices: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            devices: devices.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl Plugin for FakeMidiPlugin {
+    fn build(&self, app: &mut App) {
+        let (source, sender) = ChannelSource::with_ports(self.devices.iter().cloned());
+        let (sink, feedback) = ChannelSink::with_ports(self.devices.iter().cloned());
+
+        let plugin = self
+            .devices
+            .iter()
+            .fold(MidiControlPlugin::new(), |plugin, device| plugin.with_controller(device.clone()))
+            .with_scan_interval(Duration::ZERO)
+            .with_source(source)
+            .with_sink(sink);
+
+        app.insert_resource(FakeMidiController { sender, feedback });
+        app.add_plugins(plugin);
+    }
+}
+
+/// Send MIDI through the fake controller and run a frame
+///
+/// Requires `FakeMidiPlugin`. Each helper queues its message and calls `App::update`, so the
+/// params resources, `MidiMessageEvent`s and persistence all see it exactly like hardware input.
+pub trait MidiTestExt {
+    /// Send a message from the first fake device and run one update
+    fn send_midi(&mut self, message: MidiMessage) -> &mut Self;
+
+    /// Send a message from the named fake device and run one update
+    fn send_midi_from(&mut self, device: &str, message: MidiMessage) -> &mut Self;
+
+    /// Send a Control Change (value 0-127) and run one update
+    fn send_cc(&mut self, channel: u8, control: u8, value: u8) -> &mut Self {
+        self.send_midi(MidiMessage::ControlChange {
+            channel,
+            control,
+            value,
+        })
+    }
+
+    /// Send a Note On (velocity 1-127, 0 is a Note Off) and run one update
+    fn send_note(&mut self, channel: u8, note: u8, velocity: u8) -> &mut Self {
+        let message = if velocity == 0 {
+            MidiMessage::NoteOff {
+                channel,
+                note,
+                velocity,
+            }
+        } else {
+            MidiMessage::NoteOn {
+                channel,
+                note,
+                velocity,
+            }
+        };
+        self.send_midi(message)
+    }
+
+    /// Send a Note Off and run one update
+    fn send_note_off(&mut self, channel: u8, note: u8) -> &mut Self {
+        self.send_note(channel, note, 0)
+    }
+
+    /// Send a 14-bit pitch bend (8192 is the centre) and run one update
+    fn send_pitch_bend(&mut self, channel: u8, value: u16) -> &mut Self {
+        self.send_midi(MidiMessage::PitchBend {
+            channel,
+            value: value.min(16383),
+        })
+    }
+}
+
+impl MidiTestExt for App {
+    fn send_midi(&mut self, message: MidiMessage) -> &mut Self {
+        fake_controller(self).send(message);
+        self.update();
+        self
+    }
+
+    fn send_midi_from(&mut self, device: &str, message: MidiMessage) -> &mut Self {
+        fake_controller(self).send_from(device, message);
+        self.update();
+        self
+    }
+}
+
+fn fake_controller(app: &App) -> &FakeMidiController {
+    app.world()
+        .get_resource::<FakeMidiController>()
+        .expect("FakeMidiPlugin must be added before sending MIDI")
+}
@@ -1,24 +1,37 @@
 #[cfg(feature = "ui")]
-use crate::{MidiController, MidiParamsRegistration};
+use crate::ParamsRegistration;
+#[cfg(all(feature = "ui", feature = "midi"))]
+use crate::{MidiConnectionState, MidiController};
+#[cfg(feature = "ui")]
+use bevy::ecs::system::SystemState;
 #[cfg(feature = "ui")]
 use bevy::prelude::*;
 #[cfg(feature = "ui")]
 use bevy_egui::{egui, EguiContexts};
 
-/// System to render MIDI control UI
+/// System to render the MIDI control panel
+///
+/// Shows the connection status and devices, then the controls of every registered params type
+/// in its own collapsible section. Edits are applied to the live resources and saved like any
+/// other change. Add it to the egui pass:
+///
+/// ```rust,ignore
+/// app.add_systems(EguiPrimaryContextPass, midi_control_ui);
+/// ```
 #[cfg(feature = "ui")]
 pub fn midi_control_ui(
-    mut contexts: EguiContexts,
-    midi_controller: Res<MidiController>,
+    world: &mut World,
+    contexts: &mut SystemState<EguiContexts>,
+    mut filter: Local<String>,
 ) {
-    let Ok(ctx) = contexts.ctx_mut() else {
+    let Ok(ctx) = contexts.get_mut(world).ctx_mut().cloned() else {
         return;
     };
 
     egui::Window::new("🎛️ MIDI Live Controls")
         .default_width(450.0)
         .resizable(true)
-        .show(ctx, |ui| {
+        .show(&ctx, |ui| {
             ui.horizontal(|ui| {
                 ui.heading("Hardware MIDI Control");
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -28,87 +41,143 @@ pub fn midi_control_ui(
 
             ui.separator();
 
-            // Connection status
-            ui.horizontal(|ui| {
-                let connected = !midi_controller.values.is_empty();
-                let (icon, color) = if connected {
-                    ("🟢", egui::Color32::GREEN)
-                } else {
-                    ("🔴", egui::Color32::RED)
-                };
+            #[cfg(feature = "midi")]
+            device_status(world, ui);
 
-                ui.colored_label(color, icon);
-                ui.label(if connected {
-                    "MIDI Connected"
-                } else {
-                    "No MIDI Input"
-                });
+            ui.separator();
 
-                if !connected && ui.button("🔄 Retry").clicked() {
-                    // This would trigger a reconnection attempt
+            ui.horizontal(|ui| {
+                ui.label("🔍");
+                ui.add(egui::TextEdit::singleline(&mut *filter).hint_text("Filter by type"));
+                if !filter.is_empty() && ui.small_button("✖").clicked() {
+                    filter.clear();
                 }
             });
 
-            ui.separator();
-
             egui::ScrollArea::vertical().show(ui, |ui| {
-                // This is where we'd render UI for each registered type
-                // For now, show a placeholder that explains the auto-generation
-
-                ui.collapsing("📋 Registered MIDI Types", |ui| {
-                    for registration in inventory::iter::<MidiParamsRegistration> {
-                        ui.label(format!("✅ {}", registration.type_name));
+                let needle = filter.to_lowercase();
+                let mut registered = 0;
+                let mut shown = 0;
+
+                for registration in inventory::iter::<ParamsRegistration> {
+                    registered += 1;
+                    if !registration.type_name.to_lowercase().contains(&needle) {
+                        continue;
                     }
+                    shown += 1;
 
-                    if midi_controller.number_of_registered_types() == 0{
-                        ui.colored_label(egui::Color32::YELLOW, "No MIDI types registered yet");
-                        ui.small("Add #[derive(MidiParams)] to your Resource structs");
-                    }
-                });
-
-                ui.collapsing("🎛️ MIDI Reference", |ui| {
-                    ui.label("AKAI MIDImix Layout:");
-                    ui.separator();
+                    egui::CollapsingHeader::new(registration.type_name)
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            (registration.ui_fn)(world, ui);
+                        });
+                }
 
-                    ui.group(|ui| {
-                        ui.label("🎛️ Knobs:");
-                        ui.label("  • Row 1: CCs 1-8");
-                        ui.label("  • Row 2: CCs 9-16");
-                        ui.label("  • Row 3: CCs 17-24");
+                if registered == 0 {
+                    ui.colored_label(egui::Color32::YELLOW, "No MIDI types registered yet");
+                    ui.small("Add #[derive(MidiParams)] to your Resource structs");
+                } else if shown == 0 {
+                    ui.colored_label(egui::Color32::GRAY, format!("Nothing matches \"{}\"", *filter));
+                }
 
-                        ui.label("📏 Faders:");
-                        ui.label("  • Channels 1-8: CCs 19-26");
-                        ui.label("  • Master: CC 62");
+                #[cfg(feature = "midi")]
+                live_values(world, ui);
+            });
+        });
+}
 
-                        ui.label("🔘 Buttons:");
-                        ui.label("  • Top row: CCs 33-40");
-                        ui.label("  • Bottom row: CCs 49-56");
-                    });
-                });
+/// Connection status, with the devices that can be connected by hand
+#[cfg(all(feature = "ui", feature = "midi"))]
+fn device_status(world: &mut World, ui: &mut egui::Ui) {
+    let state = world
+        .get_resource::<MidiConnectionState>()
+        .cloned()
+        .unwrap_or_default();
+
+    // The controller only exists once MidiControlPlugin has been added
+    let Some(mut midi_controller) = world.get_resource_mut::<MidiController>() else {
+        ui.colored_label(egui::Color32::GRAY, "MIDI control is not enabled");
+        return;
+    };
 
-                ui.collapsing("📊 Live MIDI Values", |ui| {
-                    if midi_controller.values.is_empty() {
-                        ui.colored_label(egui::Color32::GRAY, "No MIDI input received");
-                        ui.small("Turn a knob or move a fader to see values here");
-                    } else {
-                        ui.columns(4, |columns| {
-                            for (&cc, &value) in &midi_controller.values {
-                                let col_idx = cc as usize % 4;
-                                let mapping = midi_controller.get_mappings().get(&cc);
-
-                                let display = if let Some(mapping) = mapping {
-                                    format!("CC{} ({}): {:.2}", cc, mapping.field_name, value)
-                                } else {
-                                    format!("CC{}: {:.2}", cc, value)
-                                };
-
-                                columns[col_idx].label(display);
-                            }
-                        });
-                    }
-                });
+    ui.horizontal(|ui| {
+        let (icon, color, status) = match &state {
+            MidiConnectionState::Connected(ports) => {
+                ("🟢", egui::Color32::GREEN, format!("Connected: {}", ports.join(", ")))
+            }
+            MidiConnectionState::Connecting => {
+                ("🟡", egui::Color32::YELLOW, "Connecting...".to_string())
+            }
+            MidiConnectionState::Failed(e) => ("🔴", egui::Color32::RED, e.to_string()),
+            MidiConnectionState::Disconnected => {
+                ("🔴", egui::Color32::RED, "No MIDI Input".to_string())
+            }
+        };
+
+        ui.colored_label(color, icon);
+        ui.label(status);
+
+        if !state.is_connected() && ui.button("🔄 Retry").clicked() {
+            midi_controller.request_scan();
+        }
+    });
+
+    // Ports found by the last device scan
+    ui.collapsing("🔌 Available Devices", |ui| {
+        let ports = midi_controller.available_ports().to_vec();
+
+        if ports.is_empty() {
+            ui.colored_label(egui::Color32::GRAY, "No MIDI devices found");
+        }
+
+        for port in ports {
+            ui.horizontal(|ui| {
+                ui.label(&port);
+                if midi_controller.connected_devices().contains(&port) {
+                    ui.small("(connected)");
+                } else if ui.small_button("Connect").clicked() {
+                    midi_controller.connect_device(port.clone());
+                }
             });
+        }
+    });
+}
+
+/// Last value received from every control, with the field it drives
+#[cfg(all(feature = "ui", feature = "midi"))]
+fn live_values(world: &World, ui: &mut egui::Ui) {
+    let Some(midi_controller) = world.get_resource::<MidiController>() else {
+        return;
+    };
+
+    ui.collapsing("📊 Live MIDI Values", |ui| {
+        if midi_controller.values.is_empty() {
+            ui.colored_label(egui::Color32::GRAY, "No MIDI input received");
+            ui.small("Turn a knob or move a fader to see values here");
+            return;
+        }
+
+        ui.columns(4, |columns| {
+            for (index, (&(device, channel, control), &value)) in
+                midi_controller.values.iter().enumerate()
+            {
+                let col_idx = index % 4;
+                let mapping = midi_controller.find_mapping(device, channel, control);
+                let device_name = midi_controller.device_name(device).unwrap_or_default();
+
+                let display = if let Some(mapping) = mapping {
+                    format!(
+                        "{} {} ch{} ({}): {:.2}",
+                        device_name, control, channel, mapping.field_name, value
+                    )
+                } else {
+                    format!("{} {} ch{}: {:.2}", device_name, control, channel, value)
+                };
+
+                columns[col_idx].label(display);
+            }
         });
+    });
 }
 
 // Dummy UI function for when ui feature is disabled
@@ -0,0 +1,33 @@
+use std::ops::Deref;
+use std::path::Path;
+
+/// Persistence file in the temp dir, removed again when the test is done with it
+pub struct TempPersistPath(String);
+
+/// Fresh persistence file path, unique to the test name and process
+pub fn temp_persist_path(name: &str) -> TempPersistPath {
+    let path = std::env::temp_dir().join(format!("bevy_midi_params_{}_{}.ron", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    TempPersistPath(path.to_string_lossy().into_owned())
+}
+
+impl Deref for TempPersistPath {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempPersistPath {
+    fn as_ref(&self) -> &Path {
+        Path::new(&self.0)
+    }
+}
+
+impl Drop for TempPersistPath {
+    fn drop(&mut self) {
+        // Tests that never save leave no file behind
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
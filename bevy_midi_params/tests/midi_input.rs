@@ -0,0 +1,784 @@
+#![cfg(feature = "midi")]
+
+mod common;
+
+use bevy::prelude::*;
+use bevy_midi_params::prelude::*;
+use bevy_midi_params::testing::{FakeMidiController, FakeMidiPlugin, MidiTestExt, FAKE_PORT_NAME};
+use bevy::time::TimeUpdateStrategy;
+use bevy_midi_params::{
+    ChannelSource, DeviceChange, MidiConnectionState, MidiConnector, MidiDeviceConnected,
+    MidiDeviceDisconnected, MidiPersistFile, MidiResult, MidiSmoothing, MidiSource, MidiTriggered,
+    OpenedPort, ReceivedMessage, SentMessage,
+};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::temp_persist_path;
+
+#[derive(Resource, MidiParams)]
+struct MixerParams {
+    #[midi(7, 0.0..10.0)]
+    pub volume: f32,
+
+    #[midi(note = 36, button)]
+    pub muted: bool,
+
+    #[midi(note = 37, momentary, channel = 9)]
+    pub solo: bool,
+}
+
+impl Default for MixerParams {
+    fn default() -> Self {
+        Self {
+            volume: 5.0,
+            muted: false,
+            solo: false,
+        }
+    }
+}
+
+#[derive(Resource, Default, MidiParams)]
+struct DeskParams {
+    #[midi(cc = 7, device = "midimix")]
+    pub dimmer: f32,
+
+    #[midi(cc = 7, device = "Launch Control")]
+    pub speed: f32,
+}
+
+#[derive(Resource, MidiParams)]
+struct PickupParams {
+    #[midi(cc = 8, 0.0..10.0, device = "Launch Control", takeover = "pickup")]
+    pub fade: f32,
+}
+
+impl Default for PickupParams {
+    fn default() -> Self {
+        Self { fade: 5.0 }
+    }
+}
+
+#[derive(Resource, MidiParams)]
+struct EncoderParams {
+    #[midi(cc = 9, 0.0..10.0, device = "Launch Control", relative = "twos_complement", step = 0.5)]
+    pub zoom: f32,
+}
+
+impl Default for EncoderParams {
+    fn default() -> Self {
+        Self { zoom: 5.0 }
+    }
+}
+
+#[derive(Resource, MidiParams)]
+struct CurveParams {
+    #[midi(cc = 10, 20.0..20000.0, device = "Launch Control", curve = "logarithmic")]
+    pub cutoff: f32,
+
+    #[midi(cc = 11, 0.0..100.0, device = "Launch Control", curve_table = [0.0, 0.1, 1.0])]
+    pub glow: f32,
+}
+
+impl Default for CurveParams {
+    fn default() -> Self {
+        Self {
+            cutoff: 20.0,
+            glow: 0.0,
+        }
+    }
+}
+
+#[derive(Resource, Default, MidiParams)]
+struct SmoothedParams {
+    #[midi(cc = 12, 0.0..10.0, device = "Launch Control", smooth = 0.5)]
+    pub exposure: f32,
+}
+
+#[derive(Resource, MidiParams)]
+struct SteppedParams {
+    #[midi(cc = 13, 0..=10, device = "Launch Control")]
+    pub quality: i32,
+}
+
+impl Default for SteppedParams {
+    fn default() -> Self {
+        Self { quality: 5 }
+    }
+}
+
+#[derive(Resource, Default, MidiParams)]
+struct PadParams {
+    #[midi(note = 40, trigger, device = "Launch Control")]
+    pub reset: (),
+
+    #[midi(note = [41, 42, 43], radio, device = "Launch Control")]
+    pub bank: u8,
+}
+
+#[derive(Resource, MidiParams)]
+struct LightParams {
+    #[midi(cc = [20, 21], -1.0..1.0, device = "Launch Control")]
+    pub aim: Vec2,
+
+    #[midi(cc = [22, 23, 24], color = "hsv", device = "Launch Control")]
+    pub tint: Color,
+
+    #[midi(cc = [25, 26, 27], device = "Launch Control")]
+    pub rotation: Quat,
+}
+
+impl Default for LightParams {
+    fn default() -> Self {
+        Self {
+            aim: Vec2::ZERO,
+            tint: Color::WHITE,
+            rotation: Quat::IDENTITY,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, MidiEnum)]
+enum LightMode {
+    #[default]
+    Off,
+    Steady,
+    Strobe,
+}
+
+#[derive(Resource, Default, MidiParams)]
+struct ChoiceParams {
+    #[midi(cc = 28, choice, device = "Launch Control")]
+    pub mode: LightMode,
+
+    #[midi(note = 60, choice, device = "Launch Control")]
+    pub fallback: LightMode,
+}
+
+#[derive(Resource, Default, MidiParams)]
+struct SceneParams {
+    #[midi(note = [70, 71, 72], radio, device = "Launch Control")]
+    pub mode: LightMode,
+}
+
+/// Source whose device scans hang while `stalled` is set, like a stuck OS MIDI service
+struct StalledSource {
+    inner: ChannelSource,
+    stalled: Arc<AtomicBool>,
+}
+
+struct StalledConnector {
+    inner: Box<dyn MidiConnector>,
+    stalled: Arc<AtomicBool>,
+}
+
+impl MidiSource for StalledSource {
+    fn connector(&self) -> Box<dyn MidiConnector> {
+        Box::new(StalledConnector {
+            inner: self.inner.connector(),
+            stalled: self.stalled.clone(),
+        })
+    }
+
+    fn attach(&mut self, port: OpenedPort) {
+        self.inner.attach(port);
+    }
+
+    fn disconnect(&mut self, port_name: &str) {
+        self.inner.disconnect(port_name);
+    }
+
+    fn poll(&mut self, messages: &mut Vec<ReceivedMessage>) {
+        self.inner.poll(messages);
+    }
+}
+
+impl MidiConnector for StalledConnector {
+    fn available_ports(&mut self) -> MidiResult<Vec<String>> {
+        while self.stalled.load(Ordering::SeqCst) {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        self.inner.available_ports()
+    }
+
+    fn connect(&mut self, port_name: &str) -> MidiResult<OpenedPort> {
+        self.inner.connect(port_name)
+    }
+}
+
+fn test_app(path: &str) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        ParamsPersistencePlugin::new().with_persist(path),
+        FakeMidiPlugin::default(),
+    ));
+    app.update();
+    app
+}
+
+fn launch_control_app(path: &str) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        ParamsPersistencePlugin::new().with_persist(path),
+        FakeMidiPlugin::with_devices(["Launch Control XL"]),
+    ));
+    app.update();
+    app
+}
+
+fn drain_events<E: Event>(app: &mut App) -> Vec<E> {
+    app.world_mut().resource_mut::<Events<E>>().drain().collect()
+}
+
+#[test]
+fn cc_updates_params_and_is_persisted() {
+    let path = temp_persist_path("cc_input");
+    let mut app = test_app(&path);
+
+    app.send_cc(0, 7, 127);
+    assert_eq!(app.world().resource::<MixerParams>().volume, 10.0);
+
+    let file = MidiPersistFile::load_from_file(&path).unwrap();
+    let saved = file.get_type_data("MixerParams").unwrap();
+    assert_eq!(saved.get::<f32>("volume"), Some(10.0));
+}
+
+#[test]
+fn notes_drive_buttons_on_their_channel() {
+    let path = temp_persist_path("note_input");
+    let mut app = test_app(&path);
+
+    app.send_note(0, 36, 100).send_note_off(0, 36);
+    assert!(app.world().resource::<MixerParams>().muted);
+
+    app.send_note(0, 37, 100);
+    assert!(!app.world().resource::<MixerParams>().solo);
+
+    app.send_note(9, 37, 100);
+    assert!(app.world().resource::<MixerParams>().solo);
+    app.send_note_off(9, 37);
+    assert!(!app.world().resource::<MixerParams>().solo);
+}
+
+#[test]
+fn every_message_is_forwarded_as_an_event() {
+    let path = temp_persist_path("events");
+    let mut app = test_app(&path);
+
+    app.send_pitch_bend(3, 8192);
+
+    let events = app.world().resource::<Events<MidiMessageEvent>>();
+    let received: Vec<_> = events.iter_current_update_events().cloned().collect();
+    assert_eq!(
+        received,
+        vec![MidiMessageEvent {
+            device: FAKE_PORT_NAME.to_string(),
+            message: MidiMessage::PitchBend {
+                channel: 3,
+                value: 8192
+            }
+        }]
+    );
+}
+
+#[test]
+fn device_mappings_only_follow_their_device() {
+    let path = temp_persist_path("devices");
+    let mut app = App::new();
+    app.add_plugins((
+        ParamsPersistencePlugin::new().with_persist(path.to_string()),
+        FakeMidiPlugin::with_devices(["MIDImix", "Launch Control XL"]),
+    ));
+    app.update();
+
+    let cc7 = |value| MidiMessage::ControlChange {
+        channel: 0,
+        control: 7,
+        value,
+    };
+
+    app.send_midi_from("MIDImix", cc7(127));
+    let desk = app.world().resource::<DeskParams>();
+    assert_eq!((desk.dimmer, desk.speed), (1.0, 0.0));
+
+    app.send_midi_from("Launch Control XL", cc7(0));
+    let desk = app.world().resource::<DeskParams>();
+    assert_eq!((desk.dimmer, desk.speed), (1.0, 0.0));
+
+    app.send_midi_from("Launch Control XL", cc7(127));
+    assert_eq!(app.world().resource::<DeskParams>().speed, 1.0);
+
+    // Omni mappings respond to every device
+    app.send_midi_from("Launch Control XL", cc7(0));
+    assert_eq!(app.world().resource::<MixerParams>().volume, 0.0);
+}
+
+#[test]
+fn values_are_sent_back_to_the_device() {
+    let path = temp_persist_path("feedback");
+    let mut app = test_app(&path);
+    let fake = app.world().resource::<FakeMidiController>().clone();
+    let sent = |message| SentMessage {
+        port: FAKE_PORT_NAME.to_string(),
+        message,
+    };
+
+    // A newly connected device is brought up to date with every mapped field
+    assert_eq!(
+        fake.take_feedback(),
+        vec![
+            sent(MidiMessage::ControlChange {
+                channel: 0,
+                control: 7,
+                value: 64
+            }),
+            sent(MidiMessage::NoteOff {
+                channel: 0,
+                note: 36,
+                velocity: 0
+            }),
+            sent(MidiMessage::NoteOff {
+                channel: 9,
+                note: 37,
+                velocity: 0
+            }),
+        ]
+    );
+
+    // Changes made outside MIDI (UI, presets) only send the fields that changed
+    app.world_mut().resource_mut::<MixerParams>().muted = true;
+    app.update();
+    assert_eq!(
+        fake.take_feedback(),
+        vec![sent(MidiMessage::NoteOn {
+            channel: 0,
+            note: 36,
+            velocity: 127
+        })]
+    );
+
+    app.update();
+    assert!(fake.take_feedback().is_empty());
+}
+
+#[test]
+fn pickup_knobs_leave_values_alone_until_they_reach_them() {
+    let path = temp_persist_path("pickup");
+    let mut app = launch_control_app(&path);
+
+    let fade = |app: &App| app.world().resource::<PickupParams>().fade;
+
+    app.send_cc(0, 8, 0).send_cc(0, 8, 38);
+    assert_eq!(fade(&app), 5.0);
+
+    // Crossing the current value picks it up
+    app.send_cc(0, 8, 89);
+    assert_eq!(fade(&app), 89.0 / 127.0 * 10.0);
+    app.send_cc(0, 8, 0);
+    assert_eq!(fade(&app), 0.0);
+}
+
+#[test]
+fn encoders_nudge_the_current_value() {
+    let path = temp_persist_path("encoder");
+    let mut app = launch_control_app(&path);
+    let zoom = |app: &App| app.world().resource::<EncoderParams>().zoom;
+
+    app.send_cc(0, 9, 1).send_cc(0, 9, 1);
+    assert_eq!(zoom(&app), 6.0);
+
+    app.send_cc(0, 9, 125);
+    assert_eq!(zoom(&app), 4.5);
+
+    app.send_cc(0, 9, 63);
+    assert_eq!(zoom(&app), 10.0);
+}
+
+#[test]
+fn curves_shape_knob_ranges() {
+    let path = temp_persist_path("curves");
+    let mut app = launch_control_app(&path);
+    let curves = |app: &App| {
+        let curves = app.world().resource::<CurveParams>();
+        (curves.cutoff, curves.glow)
+    };
+
+    app.send_cc(0, 10, 127).send_cc(0, 11, 127);
+    assert_eq!(curves(&app), (20000.0, 100.0));
+
+    app.send_cc(0, 10, 0).send_cc(0, 11, 0);
+    assert_eq!(curves(&app), (20.0, 0.0));
+
+    // A third of the knob covers the first decade of frequency
+    app.send_cc(0, 10, 42);
+    let (cutoff, _) = curves(&app);
+    assert!((cutoff - 20.0 * 1000f32.powf(42.0 / 127.0)).abs() < 0.01);
+    assert!((190.0..210.0).contains(&cutoff));
+}
+
+#[test]
+fn integer_fields_step_through_knob_zones() {
+    let path = temp_persist_path("integer");
+    let mut app = launch_control_app(&path);
+    let quality = |app: &App| app.world().resource::<SteppedParams>().quality;
+
+    app.send_cc(0, 13, 127);
+    assert_eq!(quality(&app), 10);
+
+    // The first of 11 equal zones covers CCs 0-11
+    app.send_cc(0, 13, 11);
+    assert_eq!(quality(&app), 0);
+    app.send_cc(0, 13, 12);
+    assert_eq!(quality(&app), 1);
+
+    app.send_cc(0, 13, 64);
+    assert_eq!(quality(&app), 5);
+
+    let file = MidiPersistFile::load_from_file(&path).unwrap();
+    let saved = file.get_type_data("SteppedParams").unwrap();
+    assert_eq!(saved.get::<i32>("quality"), Some(5));
+}
+
+#[test]
+fn smoothed_fields_glide_to_their_target() {
+    let path = temp_persist_path("smoothing");
+    let mut app = launch_control_app(&path);
+    app.add_plugins(bevy::time::TimePlugin)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)));
+    app.update();
+
+    let exposure = |app: &App| app.world().resource::<SmoothedParams>().exposure;
+    let target = |app: &App| {
+        app.world()
+            .resource::<MidiSmoothing<SmoothedParams>>()
+            .target("exposure")
+    };
+    let saved = || {
+        MidiPersistFile::load_from_file(&path)
+            .ok()
+            .and_then(|file| file.get_type_data("SmoothedParams")?.get::<f32>("exposure"))
+    };
+
+    app.send_cc(0, 12, 127);
+    assert_eq!(target(&app), Some(10.0));
+    let first_step = exposure(&app);
+    assert!(first_step > 0.0 && first_step < 10.0);
+
+    app.update();
+    assert!(exposure(&app) > first_step);
+    // The file is only written once the glide has arrived
+    assert_eq!(saved(), None);
+
+    for _ in 0..30 {
+        app.update();
+    }
+    assert_eq!(exposure(&app), 10.0);
+    assert_eq!(target(&app), None);
+    assert_eq!(saved(), Some(10.0));
+}
+
+#[test]
+fn triggers_fire_events_and_radio_buttons_select_a_value() {
+    let path = temp_persist_path("pads");
+    let mut app = launch_control_app(&path);
+    let fake = app.world().resource::<FakeMidiController>().clone();
+    let pad = |note, on| SentMessage {
+        port: "Launch Control XL".to_string(),
+        message: if on {
+            MidiMessage::NoteOn {
+                channel: 0,
+                note,
+                velocity: 127,
+            }
+        } else {
+            MidiMessage::NoteOff {
+                channel: 0,
+                note,
+                velocity: 0,
+            }
+        },
+    };
+    let initial = fake.take_feedback();
+    assert!([pad(41, true), pad(42, false), pad(43, false)]
+        .iter()
+        .all(|message| initial.contains(message)));
+
+    // Only the pressed button lights up
+    app.send_note(0, 42, 100).send_note_off(0, 42);
+    assert_eq!(app.world().resource::<PadParams>().bank, 1);
+    assert_eq!(fake.take_feedback(), vec![pad(41, false), pad(42, true)]);
+
+    app.send_note(0, 40, 100).send_note_off(0, 40);
+    let fired: Vec<_> = drain_events::<MidiTriggered<PadParams>>(&mut app)
+        .into_iter()
+        .map(|trigger| trigger.field)
+        .collect();
+    assert_eq!(fired, vec!["reset"]);
+
+    // The selection is persisted, the trigger holds nothing to persist
+    let file = MidiPersistFile::load_from_file(&path).unwrap();
+    let saved = file.get_type_data("PadParams").unwrap();
+    assert_eq!(saved.get::<u8>("bank"), Some(1));
+    assert!(!saved.values.contains_key("reset"));
+}
+
+#[test]
+fn components_follow_one_control_each() {
+    let path = temp_persist_path("components");
+    let mut app = launch_control_app(&path);
+
+    app.send_cc(0, 20, 127).send_cc(0, 21, 0);
+    app.send_cc(0, 22, 0).send_cc(0, 23, 127).send_cc(0, 24, 127);
+    app.send_cc(0, 25, 32);
+
+    let lights = app.world().resource::<LightParams>();
+    assert_eq!(lights.aim, Vec2::new(1.0, -1.0));
+    assert_eq!(Srgba::from(lights.tint).to_hex(), "#FF0000");
+    let yaw = (-180.0 + 360.0 * 32.0 / 127.0f32).to_radians();
+    assert!(lights.rotation.angle_between(Quat::from_rotation_y(yaw)) < 1e-3);
+
+    let file = MidiPersistFile::load_from_file(&path).unwrap();
+    let saved = file.get_type_data("LightParams").unwrap();
+    assert_eq!(saved.get::<[f32; 2]>("aim"), Some([1.0, -1.0]));
+    let tint = saved.get::<HashMap<String, f32>>("tint").unwrap();
+    for (component, expected) in [("hue", 0.0), ("saturation", 1.0), ("value", 1.0), ("alpha", 1.0)] {
+        assert!((tint[component] - expected).abs() < 1e-4, "{} is {}", component, tint[component]);
+    }
+
+    let mut restored = LightParams::default();
+    restored.apply_persist_data(saved).unwrap();
+    assert_eq!(restored.aim, Vec2::new(1.0, -1.0));
+    assert_eq!(Srgba::from(restored.tint).to_hex(), "#FF0000");
+}
+
+#[test]
+fn locked_rotations_leave_yaw_and_roll_knobs_alone() {
+    let path = temp_persist_path("gimbal_lock");
+    let mut app = launch_control_app(&path);
+    let fake = app.world().resource::<FakeMidiController>().clone();
+
+    app.send_cc(0, 25, 96).send_cc(0, 27, 32);
+    fake.take_feedback();
+
+    // At a pitch of 90 degrees only the sum of yaw and roll is known
+    app.send_cc(0, 26, 127);
+    let resent: Vec<_> = fake
+        .take_feedback()
+        .into_iter()
+        .filter_map(|sent| match sent.message {
+            MidiMessage::ControlChange { control, .. } => Some(control),
+            _ => None,
+        })
+        .collect();
+    assert!(!resent.contains(&25) && !resent.contains(&27), "resent {:?}", resent);
+}
+
+#[test]
+fn choices_follow_knob_zones_and_cycle_on_presses() {
+    let path = temp_persist_path("choices");
+    let mut app = launch_control_app(&path);
+    let choices = |app: &App| {
+        let choices = app.world().resource::<ChoiceParams>();
+        (choices.mode, choices.fallback)
+    };
+
+    app.send_cc(0, 28, 64);
+    assert_eq!(choices(&app).0, LightMode::Steady);
+    app.send_cc(0, 28, 127);
+    assert_eq!(choices(&app).0, LightMode::Strobe);
+
+    for expected in [LightMode::Steady, LightMode::Strobe, LightMode::Off] {
+        app.send_note(0, 60, 100).send_note_off(0, 60);
+        assert_eq!(choices(&app).1, expected);
+    }
+
+    let file = MidiPersistFile::load_from_file(&path).unwrap();
+    let saved = file.get_type_data("ChoiceParams").unwrap();
+    assert_eq!(saved.get::<String>("mode").as_deref(), Some("Strobe"));
+
+    let mut data = saved.clone();
+    data.insert("fallback", "Disco");
+    let mut restored = ChoiceParams::default();
+    let err = restored.apply_persist_data(&data).unwrap_err();
+    assert!(err.to_string().contains("Disco"));
+    assert_eq!(restored.mode, LightMode::Strobe);
+}
+
+#[test]
+fn radio_buttons_select_enum_variants() {
+    let path = temp_persist_path("scene");
+    let mut app = launch_control_app(&path);
+
+    app.send_note(0, 72, 100).send_note_off(0, 72);
+    assert_eq!(app.world().resource::<SceneParams>().mode, LightMode::Strobe);
+
+    let file = MidiPersistFile::load_from_file(&path).unwrap();
+    let saved = file.get_type_data("SceneParams").unwrap();
+    assert_eq!(saved.get::<String>("mode").as_deref(), Some("Strobe"));
+
+    let mut restored = SceneParams::default();
+    restored.apply_persist_data(saved).unwrap();
+    assert_eq!(restored.mode, LightMode::Strobe);
+}
+
+#[test]
+fn unplugged_devices_are_reported_and_reconnected() {
+    let path = temp_persist_path("hot_plug");
+    let mut app = test_app(&path);
+    let fake_device = || FAKE_PORT_NAME.to_string();
+
+    let connected = drain_events::<MidiDeviceConnected>(&mut app);
+    assert_eq!(connected, vec![MidiDeviceConnected { device: fake_device() }]);
+    assert_eq!(
+        *app.world().resource::<MidiConnectionState>(),
+        MidiConnectionState::Connected(vec![fake_device()])
+    );
+
+    app.world().resource::<FakeMidiController>().unplug(FAKE_PORT_NAME);
+    app.update();
+    let disconnected = drain_events::<MidiDeviceDisconnected>(&mut app);
+    assert_eq!(disconnected, vec![MidiDeviceDisconnected { device: fake_device() }]);
+    assert!(app.world().resource::<MidiController>().connected_devices().is_empty());
+    assert_eq!(
+        *app.world().resource::<MidiConnectionState>(),
+        MidiConnectionState::Disconnected
+    );
+
+    app.world().resource::<FakeMidiController>().plug(FAKE_PORT_NAME);
+    app.send_cc(0, 7, 0);
+    let connected = drain_events::<MidiDeviceConnected>(&mut app);
+    assert_eq!(connected, vec![MidiDeviceConnected { device: fake_device() }]);
+    assert_eq!(app.world().resource::<MixerParams>().volume, 0.0);
+}
+
+#[test]
+fn picked_ports_are_connected_on_the_next_update() {
+    let path = temp_persist_path("pick_port");
+    let mut app = test_app(&path);
+
+    app.world().resource::<FakeMidiController>().plug("Spare Keyboard");
+    app.update();
+
+    let controller = app.world().resource::<MidiController>();
+    assert_eq!(
+        controller.available_ports(),
+        vec![FAKE_PORT_NAME.to_string(), "Spare Keyboard".to_string()]
+    );
+    assert_eq!(controller.connected_devices(), [FAKE_PORT_NAME.to_string()]);
+
+    app.world_mut()
+        .resource_mut::<MidiController>()
+        .connect_device("Spare Keyboard");
+    app.update();
+
+    assert_eq!(
+        *app.world().resource::<MidiConnectionState>(),
+        MidiConnectionState::Connected(vec![FAKE_PORT_NAME.to_string(), "Spare Keyboard".to_string()])
+    );
+}
+
+#[test]
+fn every_port_is_connected_without_preferred_controllers() {
+    let (source, sender) = ChannelSource::with_ports(["MIDImix", "Launch Control XL"]);
+    let mut app = App::new();
+    app.add_plugins(
+        MidiControlPlugin::new()
+            .with_source(source)
+            .with_scan_interval(Duration::ZERO),
+    );
+    app.update();
+
+    assert_eq!(
+        *app.world().resource::<MidiConnectionState>(),
+        MidiConnectionState::Connected(vec!["MIDImix".to_string(), "Launch Control XL".to_string()])
+    );
+
+    sender.plug("Pads");
+    app.update();
+    assert_eq!(
+        app.world().resource::<MidiController>().connected_devices(),
+        ["MIDImix".to_string(), "Launch Control XL".to_string(), "Pads".to_string()]
+    );
+}
+
+#[test]
+fn stalled_backend_times_out_without_blocking_frames() {
+    let (source, _sender) = ChannelSource::with_ports(["Slow Port"]);
+    let stalled = Arc::new(AtomicBool::new(true));
+
+    let mut app = App::new();
+    app.add_plugins(
+        MidiControlPlugin::new()
+            .with_source(StalledSource {
+                inner: source,
+                stalled: stalled.clone(),
+            })
+            .with_connect_timeout(Duration::ZERO),
+    );
+    app.update();
+
+    assert_eq!(
+        *app.world().resource::<MidiConnectionState>(),
+        MidiConnectionState::Failed(MidiError::ConnectionTimeout(Duration::ZERO))
+    );
+    // The stuck scan is abandoned, so the next one can start
+    assert!(!app.world().resource::<MidiController>().is_refreshing());
+
+    stalled.store(false, Ordering::SeqCst);
+}
+
+#[test]
+fn open_devices_keep_delivering_while_a_scan_is_stalled() {
+    let (source, sender) = ChannelSource::with_ports(["Slow Port"]);
+    let stalled = Arc::new(AtomicBool::new(false));
+
+    let mut app = App::new();
+    app.add_plugins(
+        MidiControlPlugin::new()
+            .with_source(StalledSource {
+                inner: source,
+                stalled: stalled.clone(),
+            })
+            .no_auto_connect(),
+    );
+    let mut controller = app.world_mut().resource_mut::<MidiController>();
+    controller.connect_midi().unwrap();
+
+    stalled.store(true, Ordering::SeqCst);
+    controller.start_refresh();
+    assert_eq!(
+        controller.poll_refresh(Duration::ZERO),
+        Some(Err(MidiError::ConnectionTimeout(Duration::ZERO)))
+    );
+    controller.start_refresh();
+    assert!(controller.is_refreshing());
+
+    let message = MidiMessage::ControlChange {
+        channel: 0,
+        control: 7,
+        value: 100,
+    };
+    sender.send(message.clone());
+    app.update();
+    assert_eq!(
+        app.world().resource::<MidiController>().messages(),
+        [ReceivedMessage {
+            port: "Slow Port".to_string(),
+            message,
+        }]
+    );
+
+    // Once the backend responds the new scan picks up devices again
+    sender.plug("Pads");
+    stalled.store(false, Ordering::SeqCst);
+    let result = loop {
+        let mut controller = app.world_mut().resource_mut::<MidiController>();
+        if let Some(result) = controller.poll_refresh(Duration::from_secs(5)) {
+            break result;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    };
+    assert_eq!(result, Ok(vec![DeviceChange::Connected("Pads".to_string())]));
+}
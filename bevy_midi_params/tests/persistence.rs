@@ -0,0 +1,97 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_midi_params::prelude::*;
+use bevy_midi_params::{MidiPersistFile, PersistData};
+use common::temp_persist_path;
+
+#[derive(Resource, MidiParams)]
+struct RestoredParams {
+    #[midi(1, 0.0..10.0)]
+    pub speed: f32,
+
+    #[midi(persist, button)]
+    pub enabled: bool,
+}
+
+impl Default for RestoredParams {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            enabled: false,
+        }
+    }
+}
+
+#[test]
+fn persisted_values_are_restored_on_startup() {
+    let path = temp_persist_path("restore");
+
+    let mut data = PersistData::new();
+    data.insert("speed", 7.5f32);
+    data.insert("enabled", true);
+    let mut file = MidiPersistFile::new();
+    file.set_type_data("RestoredParams".to_string(), data);
+    file.save_to_file(&path).unwrap();
+
+    let mut app = App::new();
+    app.add_plugins(ParamsPersistencePlugin::new().with_persist(path.to_string()));
+    app.update();
+
+    let params = app.world().resource::<RestoredParams>();
+    assert_eq!(params.speed, 7.5);
+    assert!(params.enabled);
+}
+
+#[test]
+#[allow(deprecated)]
+fn legacy_plugin_restores_persisted_values() {
+    let path = temp_persist_path("legacy");
+
+    let mut data = PersistData::new();
+    data.insert("speed", 2.5f32);
+    let mut file = MidiPersistFile::new();
+    file.set_type_data("RestoredParams".to_string(), data);
+    file.save_to_file(&path).unwrap();
+
+    let mut app = App::new();
+    app.add_plugins(MidiParamsPlugin::new().with_persist(path.to_string()).no_auto_connect());
+    app.update();
+
+    assert_eq!(app.world().resource::<RestoredParams>().speed, 2.5);
+}
+
+#[test]
+fn every_registered_type_is_saved_on_exit() {
+    let path = temp_persist_path("exit");
+
+    let mut app = App::new();
+    app.add_plugins(ParamsPersistencePlugin::new().with_persist(path.to_string()));
+    app.update();
+
+    // Bypassing change detection, so only the exit save can write this
+    app.world_mut().resource_mut::<RestoredParams>().bypass_change_detection().speed = 3.0;
+    app.world_mut().send_event(AppExit::Success);
+    app.update();
+
+    let file = MidiPersistFile::load_from_file(&path).unwrap();
+    let mut restored = RestoredParams::default();
+    restored
+        .apply_persist_data(file.get_type_data("RestoredParams").unwrap())
+        .unwrap();
+    assert_eq!(restored.speed, 3.0);
+}
+
+#[test]
+fn invalid_fields_are_reported_and_valid_fields_still_load() {
+    let mut data = PersistData::new();
+    data.insert("speed", "fast");
+    data.insert("enabled", true);
+
+    let mut params = RestoredParams::default();
+    let err = params.apply_persist_data(&data).unwrap_err();
+
+    assert!(err.to_string().contains("speed"));
+    assert_eq!(params.speed, 1.0);
+    assert!(params.enabled);
+}
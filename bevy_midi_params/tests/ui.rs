@@ -0,0 +1,82 @@
+#![cfg(feature = "ui")]
+
+use bevy::prelude::*;
+use bevy_midi_params::egui;
+use bevy_midi_params::prelude::*;
+use bevy_midi_params::render_params_ui;
+
+#[derive(Resource, MidiParams)]
+struct PanelParams {
+    #[midi(1, 0.0..10.0)]
+    pub speed: f32,
+
+    #[midi(2, button)]
+    pub enabled: bool,
+}
+
+impl Default for PanelParams {
+    fn default() -> Self {
+        Self {
+            speed: 5.0,
+            enabled: false,
+        }
+    }
+}
+
+fn panel_world() -> World {
+    let mut world = World::new();
+    world.insert_resource(PanelParams::default());
+    world.clear_trackers();
+    world
+}
+
+/// Run one egui frame rendering `PanelParams` the way the control panel does
+fn render_frame(world: &mut World, ctx: &egui::Context, events: Vec<egui::Event>) -> bool {
+    let mut changed = false;
+    let _ = ctx.run(
+        egui::RawInput {
+            events,
+            ..Default::default()
+        },
+        |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                changed = render_params_ui::<PanelParams>(world, ui);
+            });
+        },
+    );
+    changed
+}
+
+fn key(key: egui::Key) -> egui::Event {
+    egui::Event::Key {
+        key,
+        physical_key: None,
+        pressed: true,
+        repeat: false,
+        modifiers: egui::Modifiers::NONE,
+    }
+}
+
+#[test]
+fn rendering_leaves_params_unchanged() {
+    let mut world = panel_world();
+    let ctx = egui::Context::default();
+
+    assert!(!render_frame(&mut world, &ctx, Vec::new()));
+    assert!(!render_frame(&mut world, &ctx, Vec::new()));
+    assert!(!world.is_resource_changed::<PanelParams>());
+    assert_eq!(world.resource::<PanelParams>().speed, 5.0);
+}
+
+#[test]
+fn slider_edits_are_applied_and_reported() {
+    let mut world = panel_world();
+    let ctx = egui::Context::default();
+
+    render_frame(&mut world, &ctx, Vec::new());
+    render_frame(&mut world, &ctx, vec![key(egui::Key::Tab)]);
+    assert!(render_frame(&mut world, &ctx, vec![key(egui::Key::ArrowRight)]));
+
+    assert!(world.resource::<PanelParams>().speed > 5.0);
+    assert!(world.is_resource_changed::<PanelParams>());
+}
@@ -3,8 +3,8 @@
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::{
-    parse_macro_input, parse_quote, Data, DeriveInput, Error, Expr, Field, Fields, Lit, Meta, Path,
-    Result as SynResult, Token,
+    parse_macro_input, Data, DeriveInput, Error, Field, Fields, Lit, Meta, Result as SynResult,
+    Token,
 };
 
 /// Derive macro for MIDI parameter mapping
@@ -18,6 +18,103 @@ pub fn derive_midi_params(input: TokenStream) -> TokenStream {
     }
 }
 
+/// Derive macro for enums selectable with `#[midi(cc = 5, choice)]`
+#[proc_macro_derive(MidiEnum)]
+pub fn derive_midi_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match impl_midi_enum(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn impl_midi_enum(input: &DeriveInput) -> SynResult<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let Data::Enum(data) = &input.data else {
+        return Err(Error::new_spanned(name, "MidiEnum can only be derived for enums"));
+    };
+    if data.variants.is_empty() {
+        return Err(Error::new_spanned(name, "MidiEnum needs at least one variant"));
+    }
+
+    let mut idents = Vec::new();
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                variant,
+                "MidiEnum variants cannot hold data",
+            ));
+        }
+        idents.push(&variant.ident);
+    }
+    let names = idents.iter().map(|ident| ident.to_string());
+    let indices = 0..idents.len();
+    let from_indices = indices.clone();
+
+    let mut de_generics = input.generics.clone();
+    de_generics.params.insert(0, syn::parse_quote!('de));
+    let (de_impl_generics, _, _) = de_generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics bevy_midi_params::MidiEnum for #name #ty_generics #where_clause {
+            const VARIANTS: &'static [&'static str] = &[#(#names),*];
+
+            fn variant_index(&self) -> usize {
+                match *self {
+                    #(Self::#idents => #indices,)*
+                }
+            }
+
+            fn from_variant_index(index: usize) -> Option<Self> {
+                match index {
+                    #(#from_indices => Some(Self::#idents),)*
+                    _ => None,
+                }
+            }
+        }
+
+        // Radio groups select variants by index
+        impl #impl_generics bevy_midi_params::RadioValue for #name #ty_generics #where_clause {
+            fn from_radio_index(index: usize) -> Option<Self> {
+                bevy_midi_params::MidiEnum::from_variant_index(index)
+            }
+
+            fn radio_index(&self) -> Option<usize> {
+                Some(bevy_midi_params::MidiEnum::variant_index(self))
+            }
+        }
+
+        // Saved by variant name, so reordering variants keeps saved values
+        impl #impl_generics bevy_midi_params::serde::Serialize for #name #ty_generics #where_clause {
+            fn serialize<S>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error>
+            where
+                S: bevy_midi_params::serde::Serializer,
+            {
+                serializer.serialize_str(bevy_midi_params::MidiEnum::variant_name(self))
+            }
+        }
+
+        impl #de_impl_generics bevy_midi_params::serde::Deserialize<'de> for #name #ty_generics #where_clause {
+            fn deserialize<D>(deserializer: D) -> ::core::result::Result<Self, D::Error>
+            where
+                D: bevy_midi_params::serde::Deserializer<'de>,
+            {
+                let variant: ::std::string::String =
+                    bevy_midi_params::serde::Deserialize::deserialize(deserializer)?;
+                bevy_midi_params::MidiEnum::from_variant_name(&variant).ok_or_else(|| {
+                    <D::Error as bevy_midi_params::serde::de::Error>::unknown_variant(
+                        &variant,
+                        <Self as bevy_midi_params::MidiEnum>::VARIANTS,
+                    )
+                })
+            }
+        }
+    })
+}
+
 fn impl_midi_params(input: &DeriveInput) -> SynResult<proc_macro2::TokenStream> {
     let name = &input.ident;
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
@@ -42,6 +139,10 @@ fn impl_midi_params(input: &DeriveInput) -> SynResult<proc_macro2::TokenStream>
 
     let mut midi_mappings = Vec::new();
     let mut midi_updates = Vec::new();
+    let mut midi_values = Vec::new();
+    let mut component_updates = Vec::new();
+    let mut component_values = Vec::new();
+    let mut component_feedback = Vec::new();
     let mut ui_controls = Vec::new();
     let mut persistence_fields = Vec::new();
     let mut load_fields = Vec::new();
@@ -50,100 +151,481 @@ fn impl_midi_params(input: &DeriveInput) -> SynResult<proc_macro2::TokenStream>
     for field in fields {
         let field_name = field.ident.as_ref().unwrap();
         let field_name_str = field_name.to_string();
+        let field_ty = &field.ty;
+        // Trigger fields hold no state, they only fire events
+        let mut persisted = true;
+        // Statements that save and load the field, when it does not persist through serde
+        let mut custom_persistence = None;
 
         if let Some(midi_attr) = parse_midi_attribute(field)? {
-            let MidiAttr { cc, control_type, persist_only, .. } = midi_attr;
+            let MidiAttr {
+                control,
+                channel,
+                device,
+                takeover,
+                relative,
+                curve,
+                smooth,
+                control_type,
+            } = midi_attr;
+
+            // MIDI control (None for persist-only fields)
+            let control_option = match &control {
+                Some(control) => {
+                    let control = control.to_tokens();
+                    quote! { Some(#control) }
+                }
+                None => quote! { None },
+            };
+
+            // Restrict the mapping to a single channel when one is given (omni otherwise)
+            let with_channel = channel.map(|channel| quote! { .with_channel(#channel) });
+            // Restrict the mapping to matching devices when a pattern is given (any otherwise)
+            let with_device = device.as_ref().map(|device| quote! { .with_device(#device) });
+            // Soft takeover for ranges (jump otherwise)
+            let with_takeover = takeover.map(|takeover| {
+                let takeover = takeover.to_tokens();
+                quote! { .with_takeover(#takeover) }
+            });
+            // Endless encoder (absolute otherwise), stepping 1% of the range per detent by default
+            let with_relative = relative.map(|relative| {
+                let mode = relative.mode.to_tokens();
+                let step = match (relative.step, &control_type) {
+                    (Some(step), _) => quote! { #step },
+                    (None, ControlType::Range { min, max }) => {
+                        let step = (max - min) / 100.0;
+                        quote! { #step }
+                    }
+                    // Computed from the range of each component
+                    (None, ControlType::Components { .. }) => quote! { (max - min) / 100.0 },
+                    (None, _) => quote! { 0.01 },
+                };
+                let with_acceleration = relative
+                    .acceleration
+                    .map(|acceleration| quote! { .with_acceleration(#acceleration) });
+                quote! { .with_relative(#mode, #step) #with_acceleration }
+            });
+            // Glide toward MIDI values (jump otherwise)
+            let with_smoothing = smooth.map(|seconds| quote! { .with_smoothing(#seconds) });
+            // Response curve for ranges (linear otherwise)
+            let with_curve = curve.as_ref().map(|curve| {
+                let curve = curve.to_tokens();
+                quote! { .with_curve(#curve) }
+            });
+
+            // UI label and indicator of the control driving the field
+            let display_name = field_name_str.replace('_', " ");
+            let channel_label = channel
+                .map(|channel| format!(" ch{}", channel))
+                .unwrap_or_default();
+            let device_label = device
+                .as_ref()
+                .map(|device| format!(" on {}", device))
+                .unwrap_or_default();
+            let control_label = match (&control, &control_type) {
+                (Some(_), ControlType::Radio(controls)) => Some(
+                    controls
+                        .iter()
+                        .map(MidiControlAttr::label)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ),
+                (Some(control), _) => Some(control.label()),
+                (None, _) => None,
+            };
+            let indicator = match control_label {
+                Some(control_label) => {
+                    let indicator = format!("{}{}{}", control_label, channel_label, device_label);
+                    quote! { ui.weak(#indicator).on_hover_text("MIDI control"); }
+                }
+                None => quote! { ui.weak("persist only"); },
+            };
+            // Reset button, only shown once the value differs from the default
+            let reset = quote! {
+                if self.#field_name != defaults.#field_name
+                    && ui.small_button("⟲").on_hover_text("Reset to default").clicked()
+                {
+                    self.#field_name = ::core::clone::Clone::clone(&defaults.#field_name);
+                    ui_changed = true;
+                }
+            };
 
             match control_type {
                 ControlType::Range { min, max } => {
-                    // MIDI mapping (with optional CC)
-                    let cc_option = if let Some(cc_val) = cc {
-                        quote! { Some(#cc_val) }
-                    } else {
-                        quote! { None }
-                    };
+                    // Any RangeValue type, integers snap to whole values
+                    let range_value = quote! { <#field_ty as bevy_midi_params::RangeValue> };
                     midi_mappings.push(quote! {
-                        bevy_midi_params::MidiMapping::range(#cc_option, #field_name_str, #min, #max)
+                        mappings.push(bevy_midi_params::MidiMapping::range(#control_option, #field_name_str, #min, #max)
+                            .with_step(#range_value::STEP)
+                            #with_channel
+                            #with_device
+                            #with_takeover
+                            #with_relative
+                            #with_curve
+                            #with_smoothing);
                     });
 
-                    // MIDI update logic (only if CC is present)
-                    if let Some(cc_val) = cc {
+                    // MIDI update logic (only if a control is present), value is already scaled
+                    if control.is_some() {
                         midi_updates.push(quote! {
-                            #cc_val => {
-                                let new_value = #min + value * (#max - #min);
-                                if (self.#field_name - new_value).abs() > f32::EPSILON {
-                                    self.#field_name = new_value;
+                            #field_name_str => {
+                                if #range_value::set_range_value(&mut self.#field_name, value) {
                                     changed = true;
                                 }
                             }
                         });
+                        midi_values.push(quote! {
+                            #field_name_str => Some(#range_value::range_value(&self.#field_name)),
+                        });
                     }
 
-                    // UI control
-                    let display_name = field_name_str.replace('_', " ");
-                    let label = if let Some(cc_val) = cc {
-                        quote! { format!("{} (CC{}):", #display_name, #cc_val) }
-                    } else {
-                        quote! { format!("{} (persist only):", #display_name) }
+                    // UI control, a slider over the declared range with decimals to suit its span
+                    // (none for integers)
+                    let decimals: usize = match max - min {
+                        span if span >= 100.0 => 1,
+                        span if span >= 10.0 => 2,
+                        _ => 3,
                     };
+                    let logarithmic = matches!(curve, Some(CurveAttr::Logarithmic))
+                        .then(|| quote! { .logarithmic(true) });
                     ui_controls.push(quote! {
                         ui.horizontal(|ui| {
-                            ui.label(#label);
-                            // ui_changed |= ui.add(egui::Slider::new(&mut self.#field_name, #min..=#max)
-                            //     .text(format!("{:.3}", self.#field_name))).changed();
+                            ui.label(#display_name);
+                            ui_changed |= ui
+                                .add(
+                                    bevy_midi_params::egui::Slider::new(
+                                        &mut self.#field_name,
+                                        #range_value::from_range_value(#min)..=#range_value::from_range_value(#max),
+                                    )
+                                    .fixed_decimals(if #range_value::STEP > 0.0 { 0 } else { #decimals })
+                                    #logarithmic,
+                                )
+                                .changed();
+                            #indicator
+                            #reset
                         });
                     });
                 }
-                ControlType::Button => {
-                    // MIDI mapping (with optional CC)
-                    let cc_option = if let Some(cc_val) = cc {
-                        quote! { Some(#cc_val) }
+                ControlType::Button | ControlType::Momentary => {
+                    let (constructor, update) = if matches!(control_type, ControlType::Button) {
+                        // Toggle on press
+                        (
+                            quote! { button },
+                            quote! {
+                                if value > 0.5 {
+                                    self.#field_name = !self.#field_name;
+                                    changed = true;
+                                }
+                            },
+                        )
                     } else {
-                        quote! { None }
+                        // Follow the pressed state
+                        (
+                            quote! { momentary },
+                            quote! {
+                                let pressed = value > 0.5;
+                                if self.#field_name != pressed {
+                                    self.#field_name = pressed;
+                                    changed = true;
+                                }
+                            },
+                        )
                     };
+
                     midi_mappings.push(quote! {
-                        bevy_midi_params::MidiMapping::button(#cc_option, #field_name_str)
+                        mappings.push(bevy_midi_params::MidiMapping::#constructor(#control_option, #field_name_str)
+                            #with_channel
+                            #with_device);
                     });
 
-                    // MIDI update logic (only if CC is present)
-                    if let Some(cc_val) = cc {
+                    // MIDI update logic (only if a control is present)
+                    if control.is_some() {
                         midi_updates.push(quote! {
-                            #cc_val => {
-                                if value > 0.5 {
-                                    self.#field_name = !self.#field_name;
-                                    changed = true;
-                                }
+                            #field_name_str => {
+                                #update
                             }
                         });
+                        midi_values.push(quote! {
+                            #field_name_str => Some(if self.#field_name { 1.0 } else { 0.0 }),
+                        });
                     }
 
                     // UI control
-                    let display_name = field_name_str.replace('_', " ");
-                    let label = if let Some(cc_val) = cc {
-                        quote! { format!("{} (CC{}):", #display_name, #cc_val) }
-                    } else {
-                        quote! { format!("{} (persist only):", #display_name) }
-                    };
                     ui_controls.push(quote! {
                         ui.horizontal(|ui| {
-                            ui.label(#label);
+                            ui.label(#display_name);
                             ui_changed |= ui.checkbox(&mut self.#field_name, "").changed();
+                            #indicator
+                            #reset
+                        });
+                    });
+                }
+                ControlType::Trigger => {
+                    // Handled by the plugin, which fires MidiTriggered instead of updating the field
+                    midi_mappings.push(quote! {
+                        mappings.push(bevy_midi_params::MidiMapping::trigger(#control_option, #field_name_str)
+                            #with_channel
+                            #with_device);
+                    });
+                    persisted = false;
+
+                    ui_controls.push(quote! {
+                        ui.horizontal(|ui| {
+                            ui.label(#display_name);
+                            #indicator
+                        });
+                    });
+                }
+                ControlType::Radio(controls) => {
+                    // One mapping per button, each selecting the value at its index
+                    for (index, control) in controls.iter().enumerate() {
+                        let control = control.to_tokens();
+                        midi_mappings.push(quote! {
+                            mappings.push(bevy_midi_params::MidiMapping::radio(Some(#control), #field_name_str, #index)
+                                #with_channel
+                                #with_device);
+                        });
+                    }
+                    let count = controls.len();
+
+                    // The value is the index of the pressed button
+                    midi_updates.push(quote! {
+                        #field_name_str => {
+                            if let Some(selected) =
+                                bevy_midi_params::RadioValue::from_radio_index(value.round() as usize)
+                            {
+                                if self.#field_name != selected {
+                                    self.#field_name = selected;
+                                    changed = true;
+                                }
+                            }
+                        }
+                    });
+                    midi_values.push(quote! {
+                        #field_name_str => bevy_midi_params::RadioValue::radio_index(&self.#field_name)
+                            .map(|index| index as f32),
+                    });
+
+                    // UI control, one selectable label per button
+                    ui_controls.push(quote! {
+                        ui.horizontal(|ui| {
+                            ui.label(#display_name);
+                            for index in 0..#count {
+                                let selected =
+                                    bevy_midi_params::RadioValue::radio_index(&self.#field_name) == Some(index);
+                                if ui.selectable_label(selected, index.to_string()).clicked() && !selected {
+                                    if let Some(value) = bevy_midi_params::RadioValue::from_radio_index(index) {
+                                        self.#field_name = value;
+                                        ui_changed = true;
+                                    }
+                                }
+                            }
+                            #indicator
+                            #reset
+                        });
+                    });
+                }
+                ControlType::Components { controls, range, space } => {
+                    let value_trait = quote! { <#field_ty as bevy_midi_params::ComponentValue> };
+                    let space = space.to_tokens();
+                    let count = controls.len();
+                    let count_message =
+                        format!("'{}' needs one MIDI control per component", field_name_str);
+                    let prefix = format!("{}.", field_name_str);
+                    let range = match range {
+                        Some((min, max)) => quote! { (#min, #max) },
+                        None => quote! { #value_trait::default_range(index, #space) },
+                    };
+                    let indicators = controls
+                        .iter()
+                        .map(|control| format!("{}{}{}", control.label(), channel_label, device_label))
+                        .collect::<Vec<_>>();
+                    let controls = controls.iter().map(MidiControlAttr::to_tokens);
+
+                    // One range mapping per component, named `field.component`
+                    midi_mappings.push(quote! {
+                        const { assert!(#value_trait::COUNT == #count, #count_message) };
+                        for (index, control) in [#(#controls),*].into_iter().enumerate() {
+                            let (min, max) = #range;
+                            let field_name =
+                                format!("{}{}", #prefix, #value_trait::component_name(index, #space));
+                            mappings.push(bevy_midi_params::MidiMapping::range(Some(control), field_name, min, max)
+                                #with_channel
+                                #with_device
+                                #with_takeover
+                                #with_relative
+                                #with_curve
+                                #with_smoothing);
+                        }
+                    });
+
+                    // Mapping names are built at runtime, so they are matched before the fixed names
+                    let component_index = quote! {
+                        field_name
+                            .strip_prefix(#prefix)
+                            .and_then(|component| #value_trait::component_index(component, #space))
+                    };
+                    component_updates.push(quote! {
+                        if let Some(index) = #component_index {
+                            return #value_trait::set_component(&mut self.#field_name, index, value, #space);
+                        }
+                    });
+                    component_values.push(quote! {
+                        if let Some(index) = #component_index {
+                            return Some(#value_trait::component(&self.#field_name, index, #space));
+                        }
+                    });
+                    component_feedback.push(quote! {
+                        if let Some(index) = #component_index {
+                            if !#value_trait::is_determined(&self.#field_name, index, #space) {
+                                return None;
+                            }
+                        }
+                    });
+
+                    // UI controls, a slider per component below the field's name
+                    ui_controls.push(quote! {
+                        ui.horizontal(|ui| {
+                            ui.label(#display_name);
+                            #reset
+                        });
+                        for (index, indicator) in [#(#indicators),*].into_iter().enumerate() {
+                            let (min, max) = #range;
+                            ui.horizontal(|ui| {
+                                ui.label(format!("  {}", #value_trait::component_name(index, #space)));
+                                let field = &mut self.#field_name;
+                                ui_changed |= ui
+                                    .add(bevy_midi_params::egui::Slider::from_get_set(
+                                        min as f64..=max as f64,
+                                        |value| {
+                                            if let Some(value) = value {
+                                                #value_trait::set_component(field, index, value as f32, #space);
+                                            }
+                                            #value_trait::component(field, index, #space) as f64
+                                        },
+                                    ))
+                                    .changed();
+                                ui.weak(indicator).on_hover_text("MIDI control");
+                            });
+                        }
+                    });
+
+                    custom_persistence = Some((
+                        quote! {
+                            data.insert(#field_name_str, #value_trait::to_persist_value(&self.#field_name, #space));
+                        },
+                        quote! {
+                            match data.try_get(#field_name_str) {
+                                Ok(Some(value)) => match #value_trait::from_persist_value(&value, #space) {
+                                    Ok(value) => self.#field_name = value,
+                                    Err(e) => errors.push(format!("Invalid value for '{}': {}", #field_name_str, e)),
+                                },
+                                Ok(None) => {}
+                                Err(e) => errors.push(e.to_string()),
+                            }
+                        },
+                    ));
+                }
+                ControlType::Choice { cycle } => {
+                    let enum_trait = quote! { <#field_ty as bevy_midi_params::MidiEnum> };
+                    let constructor = if cycle { quote! { cycle } } else { quote! { choice } };
+                    midi_mappings.push(quote! {
+                        mappings.push(bevy_midi_params::MidiMapping::#constructor(
+                            #control_option,
+                            #field_name_str,
+                            #enum_trait::VARIANTS.len(),
+                        )
+                            #with_channel
+                            #with_device);
+                    });
+
+                    // MIDI update logic (only if a control is present)
+                    if control.is_some() {
+                        let update = if cycle {
+                            // Next variant on press
+                            quote! {
+                                if value > 0.5 {
+                                    if let Some(next) = #enum_trait::next_variant(&self.#field_name) {
+                                        self.#field_name = next;
+                                        changed = true;
+                                    }
+                                }
+                            }
+                        } else {
+                            // The value is the index of the variant under the knob
+                            quote! {
+                                let index = value.round() as usize;
+                                if #enum_trait::variant_index(&self.#field_name) != index {
+                                    if let Some(selected) = #enum_trait::from_variant_index(index) {
+                                        self.#field_name = selected;
+                                        changed = true;
+                                    }
+                                }
+                            }
+                        };
+                        midi_updates.push(quote! {
+                            #field_name_str => {
+                                #update
+                            }
+                        });
+                        midi_values.push(quote! {
+                            #field_name_str => Some(#enum_trait::variant_index(&self.#field_name) as f32),
+                        });
+                    }
+
+                    // UI control, a combo box listing every variant
+                    let id_salt = format!("{}::{}", name, field_name_str);
+                    ui_controls.push(quote! {
+                        ui.horizontal(|ui| {
+                            ui.label(#display_name);
+                            let selected = #enum_trait::variant_index(&self.#field_name);
+                            bevy_midi_params::egui::ComboBox::from_id_salt(#id_salt)
+                                .selected_text(#enum_trait::variant_name(&self.#field_name))
+                                .show_ui(ui, |ui| {
+                                    for (index, variant) in #enum_trait::VARIANTS.iter().enumerate() {
+                                        if ui.selectable_label(index == selected, *variant).clicked()
+                                            && index != selected
+                                        {
+                                            if let Some(value) = #enum_trait::from_variant_index(index) {
+                                                self.#field_name = value;
+                                                ui_changed = true;
+                                            }
+                                        }
+                                    }
+                                });
+                            #indicator
+                            #reset
                         });
                     });
                 }
             }
         }
 
+        if !persisted {
+            // Still counts as a use, trigger fields are never read otherwise
+            persistence_fields.push(quote! {
+                let _ = &self.#field_name;
+            });
+            continue;
+        }
+
         // Persistence for all fields (not just MIDI ones)
-        persistence_fields.push(quote! {
-            data.insert(#field_name_str, &self.#field_name);
-        });
+        if let Some((save, load)) = custom_persistence {
+            persistence_fields.push(save);
+            load_fields.push(load);
+        } else {
+            persistence_fields.push(quote! {
+                data.insert(#field_name_str, &self.#field_name);
+            });
 
-        load_fields.push(quote! {
-            if let Some(value) = data.get(#field_name_str) {
-                self.#field_name = value;
-            }
-        });
+            load_fields.push(quote! {
+                match data.try_get(#field_name_str) {
+                    Ok(Some(value)) => self.#field_name = value,
+                    Ok(None) => {}
+                    Err(e) => errors.push(e.to_string()),
+                }
+            });
+        }
 
         change_detection.push(quote! {
             (self.#field_name != old.#field_name)
@@ -154,32 +636,51 @@ fn impl_midi_params(input: &DeriveInput) -> SynResult<proc_macro2::TokenStream>
 
     let expanded = quote! {
         impl #impl_generics bevy_midi_params::PersistableParams for #name #ty_generics #where_clause {
-            #[cfg(feature = "midi")]
-            fn update_from_midi(&mut self, cc: u8, value: f32) -> bool {
-                let mut changed = false;
-                match cc {
-                    #(#midi_updates)*
-                    _ => {}
+            // Feature gates go through bevy_midi_params, the crate using the derive has its own features
+            bevy_midi_params::__if_midi! {
+                fn update_from_midi(&mut self, field_name: &str, value: f32) -> bool {
+                    #(#component_updates)*
+                    let mut changed = false;
+                    match field_name {
+                        #(#midi_updates)*
+                        _ => {}
+                    }
+                    changed
+                }
+
+                fn get_midi_value(&self, field_name: &str) -> Option<f32> {
+                    #(#component_values)*
+                    match field_name {
+                        #(#midi_values)*
+                        _ => None,
+                    }
+                }
+
+                fn get_feedback_value(&self, field_name: &str) -> Option<f32> {
+                    #(#component_feedback)*
+                    bevy_midi_params::PersistableParams::get_midi_value(self, field_name)
                 }
-                changed
             }
 
             fn get_param_mappings() -> Vec<bevy_midi_params::MidiMapping> {
-                vec![#(#midi_mappings),*]
+                let mut mappings = Vec::new();
+                #(#midi_mappings)*
+                mappings
             }
 
-            #[cfg(feature = "ui")]
-            fn render_ui(&mut self, ui: &mut egui::Ui) -> bool {
-                let mut ui_changed = false;
-                ui.heading(#type_name_str);
-                ui.separator();
-                #(#ui_controls)*
-                ui_changed
+            bevy_midi_params::__if_ui! {
+                #[allow(unused_variables)]
+                fn render_ui(&mut self, ui: &mut bevy_midi_params::egui::Ui, defaults: &Self) -> bool {
+                    let mut ui_changed = false;
+                    #(#ui_controls)*
+                    ui_changed
+                }
             }
 
-            #[cfg(not(feature = "ui"))]
-            fn render_ui(&mut self, _ui: &mut ()) -> bool {
-                false
+            bevy_midi_params::__if_not_ui! {
+                fn render_ui(&mut self, _ui: &mut (), _defaults: &Self) -> bool {
+                    false
+                }
             }
 
             fn get_type_name() -> &'static str {
@@ -192,15 +693,24 @@ fn impl_midi_params(input: &DeriveInput) -> SynResult<proc_macro2::TokenStream>
                 data
             }
 
-            fn from_persist_data(&mut self, data: &bevy_midi_params::PersistData) {
+            fn apply_persist_data(
+                &mut self,
+                data: &bevy_midi_params::PersistData,
+            ) -> bevy_midi_params::MidiResult<()> {
+                let mut errors: Vec<String> = Vec::new();
                 #(#load_fields)*
+                if errors.is_empty() {
+                    Ok(())
+                } else {
+                    Err(bevy_midi_params::MidiError::PersistenceError(errors.join("; ")))
+                }
             }
         }
 
         impl #impl_generics #name #ty_generics #where_clause {
             #[allow(dead_code)]
             fn has_changed_from(&self, old: &Self) -> bool {
-                #(#change_detection)||*
+                false #(|| #change_detection)*
             }
         }
 
@@ -210,9 +720,12 @@ fn impl_midi_params(input: &DeriveInput) -> SynResult<proc_macro2::TokenStream>
                 type_name: #type_name_str,
                 register_fn: |app: &mut bevy::prelude::App| {
                     bevy_midi_params::register_persistable_type::<#name #ty_generics>(app);
-                    #[cfg(feature = "midi")]
+                    // No-op without the "midi" feature
                     bevy_midi_params::register_midi_control::<#name #ty_generics>(app);
                 },
+                load_fn: bevy_midi_params::load_persisted_params::<#name #ty_generics>,
+                save_fn: bevy_midi_params::save_persisted_params::<#name #ty_generics>,
+                ui_fn: bevy_midi_params::render_params_ui::<#name #ty_generics>,
             }
         }
     };
@@ -224,6 +737,214 @@ fn impl_midi_params(input: &DeriveInput) -> SynResult<proc_macro2::TokenStream>
 enum ControlType {
     Range { min: f32, max: f32 },
     Button,
+    Momentary,
+    Trigger,
+    /// Buttons selecting the value at their index
+    Radio(Vec<MidiControlAttr>),
+    /// One control per component of a vector, rotation or color, with a range shared by all
+    /// components (the type's own ranges otherwise)
+    Components {
+        controls: Vec<MidiControlAttr>,
+        range: Option<(f32, f32)>,
+        space: ColorSpaceAttr,
+    },
+    /// Enum variant picked by knob zones, or the next variant on every press with `cycle`
+    Choice { cycle: bool },
+}
+
+impl ControlType {
+    /// Continuous controls, which take ranges, curves, takeover, encoders and smoothing
+    fn is_continuous(&self) -> bool {
+        matches!(self, ControlType::Range { .. } | ControlType::Components { .. })
+    }
+}
+
+/// Color space named in the attribute
+#[derive(Debug, Clone, Copy, Default)]
+enum ColorSpaceAttr {
+    #[default]
+    Hsv,
+    Hsl,
+    Rgb,
+}
+
+impl ColorSpaceAttr {
+    fn parse(lit: &syn::LitStr) -> SynResult<Self> {
+        match lit.value().as_str() {
+            "hsv" => Ok(ColorSpaceAttr::Hsv),
+            "hsl" => Ok(ColorSpaceAttr::Hsl),
+            "rgb" => Ok(ColorSpaceAttr::Rgb),
+            _ => Err(Error::new_spanned(lit, "Expected color \"hsv\", \"hsl\" or \"rgb\"")),
+        }
+    }
+
+    fn to_tokens(self) -> proc_macro2::TokenStream {
+        match self {
+            ColorSpaceAttr::Hsv => quote! { bevy_midi_params::ColorSpace::Hsv },
+            ColorSpaceAttr::Hsl => quote! { bevy_midi_params::ColorSpace::Hsl },
+            ColorSpaceAttr::Rgb => quote! { bevy_midi_params::ColorSpace::Rgb },
+        }
+    }
+}
+
+/// Soft takeover mode named in the attribute
+#[derive(Debug, Clone, Copy)]
+enum TakeoverAttr {
+    Jump,
+    Pickup,
+    ValueScaling,
+}
+
+impl TakeoverAttr {
+    fn parse(lit: &syn::LitStr) -> SynResult<Self> {
+        match lit.value().as_str() {
+            "jump" => Ok(TakeoverAttr::Jump),
+            "pickup" => Ok(TakeoverAttr::Pickup),
+            "value_scaling" => Ok(TakeoverAttr::ValueScaling),
+            _ => Err(Error::new_spanned(
+                lit,
+                "Expected takeover \"jump\", \"pickup\" or \"value_scaling\"",
+            )),
+        }
+    }
+
+    fn to_tokens(self) -> proc_macro2::TokenStream {
+        match self {
+            TakeoverAttr::Jump => quote! { bevy_midi_params::Takeover::Jump },
+            TakeoverAttr::Pickup => quote! { bevy_midi_params::Takeover::Pickup },
+            TakeoverAttr::ValueScaling => quote! { bevy_midi_params::Takeover::ValueScaling },
+        }
+    }
+}
+
+/// Endless encoder settings named in the attribute
+#[derive(Debug, Clone, Copy)]
+struct RelativeAttr {
+    mode: RelativeModeAttr,
+    step: Option<f32>,
+    acceleration: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RelativeModeAttr {
+    TwosComplement,
+    BinaryOffset,
+    SignMagnitude,
+}
+
+impl RelativeModeAttr {
+    fn parse(lit: &syn::LitStr) -> SynResult<Self> {
+        match lit.value().as_str() {
+            "twos_complement" => Ok(RelativeModeAttr::TwosComplement),
+            "binary_offset" => Ok(RelativeModeAttr::BinaryOffset),
+            "sign_magnitude" => Ok(RelativeModeAttr::SignMagnitude),
+            _ => Err(Error::new_spanned(
+                lit,
+                "Expected relative \"twos_complement\", \"binary_offset\" or \"sign_magnitude\"",
+            )),
+        }
+    }
+
+    fn to_tokens(self) -> proc_macro2::TokenStream {
+        match self {
+            RelativeModeAttr::TwosComplement => {
+                quote! { bevy_midi_params::RelativeMode::TwosComplement }
+            }
+            RelativeModeAttr::BinaryOffset => quote! { bevy_midi_params::RelativeMode::BinaryOffset },
+            RelativeModeAttr::SignMagnitude => quote! { bevy_midi_params::RelativeMode::SignMagnitude },
+        }
+    }
+}
+
+/// Response curve named in the attribute
+#[derive(Clone)]
+enum CurveAttr {
+    Linear,
+    Logarithmic,
+    Exponential(f32),
+    SCurve,
+    Function(syn::Path),
+    Table(Vec<f32>),
+}
+
+impl CurveAttr {
+    /// Parse a named curve, `power` only applies to "exponential" (2.0 when omitted)
+    fn parse(lit: &syn::LitStr, power: Option<f32>) -> SynResult<Self> {
+        let curve = match lit.value().as_str() {
+            "linear" => CurveAttr::Linear,
+            "log" | "logarithmic" => CurveAttr::Logarithmic,
+            "exp" | "exponential" => return Ok(CurveAttr::Exponential(power.unwrap_or(2.0))),
+            "s_curve" => CurveAttr::SCurve,
+            _ => {
+                return Err(Error::new_spanned(
+                    lit,
+                    "Expected curve \"linear\", \"logarithmic\", \"exponential\" or \"s_curve\"",
+                ))
+            }
+        };
+        if power.is_some() {
+            return Err(Error::new_spanned(lit, "'power' only applies to the \"exponential\" curve"));
+        }
+        Ok(curve)
+    }
+
+    fn to_tokens(&self) -> proc_macro2::TokenStream {
+        match self {
+            CurveAttr::Linear => quote! { bevy_midi_params::ResponseCurve::Linear },
+            CurveAttr::Logarithmic => quote! { bevy_midi_params::ResponseCurve::Logarithmic },
+            CurveAttr::Exponential(power) => {
+                quote! { bevy_midi_params::ResponseCurve::Exponential(#power) }
+            }
+            CurveAttr::SCurve => quote! { bevy_midi_params::ResponseCurve::SCurve },
+            CurveAttr::Function(path) => quote! { bevy_midi_params::ResponseCurve::Function(#path) },
+            CurveAttr::Table(points) => quote! { bevy_midi_params::ResponseCurve::Table(&[#(#points),*]) },
+        }
+    }
+}
+
+/// MIDI control named in the attribute
+#[derive(Debug, Clone)]
+enum MidiControlAttr {
+    Cc(u8),
+    HiResCc(u8),
+    Note(u8),
+    Nrpn(u16),
+    Rpn(u16),
+    PitchBend,
+    ChannelAftertouch,
+    PolyAftertouch(u8),
+}
+
+impl MidiControlAttr {
+    fn to_tokens(&self) -> proc_macro2::TokenStream {
+        match self {
+            MidiControlAttr::Cc(cc) => quote! { bevy_midi_params::MidiControl::Cc(#cc) },
+            MidiControlAttr::HiResCc(cc) => quote! { bevy_midi_params::MidiControl::HiResCc(#cc) },
+            MidiControlAttr::Note(note) => quote! { bevy_midi_params::MidiControl::Note(#note) },
+            MidiControlAttr::Nrpn(param) => quote! { bevy_midi_params::MidiControl::Nrpn(#param) },
+            MidiControlAttr::Rpn(param) => quote! { bevy_midi_params::MidiControl::Rpn(#param) },
+            MidiControlAttr::PitchBend => quote! { bevy_midi_params::MidiControl::PitchBend },
+            MidiControlAttr::ChannelAftertouch => {
+                quote! { bevy_midi_params::MidiControl::ChannelAftertouch }
+            }
+            MidiControlAttr::PolyAftertouch(note) => {
+                quote! { bevy_midi_params::MidiControl::PolyAftertouch(#note) }
+            }
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            MidiControlAttr::Cc(cc) => format!("CC{}", cc),
+            MidiControlAttr::HiResCc(cc) => format!("CC{}/{} 14-bit", cc, cc + 32),
+            MidiControlAttr::Note(note) => format!("Note {}", note),
+            MidiControlAttr::Nrpn(param) => format!("NRPN {}", param),
+            MidiControlAttr::Rpn(param) => format!("RPN {}", param),
+            MidiControlAttr::PitchBend => "Pitch Bend".to_string(),
+            MidiControlAttr::ChannelAftertouch => "Aftertouch".to_string(),
+            MidiControlAttr::PolyAftertouch(note) => format!("Poly Aftertouch {}", note),
+        }
+    }
 }
 
 fn parse_midi_attribute(field: &Field) -> SynResult<Option<MidiAttr>> {
@@ -253,120 +974,485 @@ fn parse_midi_meta(meta: &Meta) -> SynResult<MidiAttr> {
 // #[midi(1, 0.0..1.0)]          - CC range control
 // #[midi(2, 0.0..=5.0)]          - CC range control (inclusive)
 // #[midi(3, button)]             - CC button/toggle
+// #[midi(3, toggle)]             - Same as 'button'
+// #[midi(3, momentary)]          - CC momentary button (true while held)
+// #[midi(3, trigger)]            - One-shot button firing a MidiTriggered event (field is not persisted)
+// #[midi(note = [36, 37, 38], radio)] - Radio group, the button at index n sets an integer field to n
+// #[midi(cc = [20, 21], radio)]  - Radio group of CC buttons
+// #[midi(cc = [1, 2, 3], -1.0..1.0)] - One CC per component of a Vec2/Vec3 (x, y, z), Quat (yaw,
+//                                   pitch, roll in degrees), Rot2 (angle) or Color field
+// #[midi(cc = [1, 2, 3], color = "hsl")] - Color knobs: "hsv" (default), "hsl" or "rgb"
+// #[midi(5, 0..=10)]             - Integer field (any of i8-i64, u8-u64, usize), rounds to whole values
+// #[midi(cc = 5, choice)]        - Enum field deriving MidiEnum, the knob sweeps through the
+//                                   variants in equal zones
+// #[midi(note = 36, choice)]     - Enum field, each press selects the next variant (same as
+//                                   `choice, button` on a CC)
 // #[midi(4)]                     - CC default range 0.0..1.0
-// #[midi(note = 18, button)]     - Note-based button
+// #[midi(note = 18, button)]     - Note-based toggle (flips on note on)
+// #[midi(note = 18, momentary)]  - Note-based momentary (true while the note is held)
+// #[midi(note = 36, 0.0..1.0)]   - Note velocity mapped to a range
 // #[midi(cc = 33, button)]       - CC-based button (explicit)
+// #[midi(cc = 7, channel = 2)]   - CC on MIDI channel 2 only (0-15, omni when omitted)
+// #[midi(cc = 7, device = "MIDImix")] - Only from devices whose name contains "MIDImix"
+// #[midi(cc = 7, takeover = "pickup")] - Soft takeover: "jump" (default), "pickup" or "value_scaling"
+// #[midi(cc = 1, relative = "twos_complement")] - Endless encoder: "twos_complement", "binary_offset"
+//                                   or "sign_magnitude", optional `step = 0.05` per detent (default 1%
+//                                   of the range) and `acceleration = 1.0` for fast turns
+// #[midi(1, 0.0..5000.0, curve = "exponential", power = 3.0)] - Response curve: "linear",
+//                                   "logarithmic", "exponential" (power 2.0 by default) or "s_curve"
+// #[midi(1, 0.0..1.0, curve_fn = my_curve)] - Custom curve, a `fn(f32) -> f32` on 0.0-1.0
+// #[midi(1, 0.0..1.0, curve_table = [0.0, 0.1, 1.0])] - Curve through evenly spaced points
+// #[midi(1, 0.0..1.0, smooth = 0.1)] - Glide to new values, 95% of the way in 0.1 seconds
+// #[midi(cc = 1, hires)]         - 14-bit CC pair (MSB on CC 1, LSB on CC 33), CC must be 0-31
+// #[midi(nrpn = 1024, 0.0..1.0)] - 14-bit NRPN parameter (0-16383)
+// #[midi(rpn = 0, 0.0..1.0)]     - 14-bit RPN parameter (0-16383)
+// #[midi(pitch_bend, -1.0..1.0)] - 14-bit pitch bend, centre detent maps to the range midpoint
+// #[midi(aftertouch, 0.0..1.0)]  - Channel aftertouch (pressure)
+// #[midi(poly_aftertouch = 36)]  - Polyphonic aftertouch for a single note
 // #[midi(persist, 0.0..1.0)]     - Persist-only range (no MIDI control)
 // #[midi(persist, button)]       - Persist-only button (no MIDI control)
 // #[midi(persist)]               - Persist-only default range 0.0..1.0
 struct MidiAttr {
-    cc: Option<u8>,
+    control: Option<MidiControlAttr>,
+    channel: Option<u8>,
+    device: Option<String>,
+    takeover: Option<TakeoverAttr>,
+    relative: Option<RelativeAttr>,
+    curve: Option<CurveAttr>,
+    smooth: Option<f32>,
     control_type: ControlType,
-    is_note: bool,
-    persist_only: bool,
 }
 
 impl syn::parse::Parse for MidiAttr {
     fn parse(input: syn::parse::ParseStream) -> SynResult<Self> {
-        let mut cc = None;
-        let mut is_note = false;
+        let mut control = None;
+        let mut channel = None;
+        let mut device = None;
+        let mut takeover = None;
+        let mut relative_mode = None;
+        let mut step = None;
+        let mut acceleration = None;
+        let mut curve_name = None;
+        let mut power = None;
+        let mut curve = None;
+        let mut smooth = None;
+        let mut hires = None;
         let mut persist_only = false;
         let mut control_type = None;
+        let mut group: Option<(syn::Ident, Vec<MidiControlAttr>)> = None;
+        let mut radio = None;
+        let mut color = None;
+        let mut choice = None;
+        let mut first = true;
 
-        // Check if first token is an identifier (for named parameters)
-        if input.peek(syn::Ident) {
-            let ident: syn::Ident = input.parse()?;
-            match ident.to_string().as_str() {
-                "cc" => {
-                    let _eq: Token![=] = input.parse()?;
-                    let cc_lit: Lit = input.parse()?;
-                    cc = Some(extract_u8_from_lit(&cc_lit)?);
+        while !input.is_empty() {
+            if !first {
+                let _comma: Token![,] = input.parse()?;
+                if input.is_empty() {
+                    break;
                 }
-                "note" => {
-                    let _eq: Token![=] = input.parse()?;
-                    let note_lit: Lit = input.parse()?;
-                    cc = Some(extract_u8_from_lit(&note_lit)? + 128); // Offset notes
-                    is_note = true;
+            }
+
+            if input.peek(syn::Ident) {
+                let ident: syn::Ident = input.parse()?;
+                match ident.to_string().as_str() {
+                    "cc" | "note" => {
+                        let _eq: Token![=] = input.parse()?;
+                        let make = if ident == "cc" {
+                            MidiControlAttr::Cc
+                        } else {
+                            MidiControlAttr::Note
+                        };
+                        if input.peek(syn::token::Bracket) {
+                            // Button group, e.g. note = [36, 37, 38]
+                            let content;
+                            syn::bracketed!(content in input);
+                            let controls = content
+                                .parse_terminated(<Lit as syn::parse::Parse>::parse, Token![,])?
+                                .iter()
+                                .map(|lit| extract_u8_from_lit(lit).map(make))
+                                .collect::<SynResult<Vec<_>>>()?;
+                            control = controls.first().cloned();
+                            group = Some((ident, controls));
+                        } else {
+                            let lit: Lit = input.parse()?;
+                            control = Some(make(extract_u8_from_lit(&lit)?));
+                        }
+                    }
+                    "nrpn" | "rpn" => {
+                        let _eq: Token![=] = input.parse()?;
+                        let param_lit: Lit = input.parse()?;
+                        let param = extract_u14_from_lit(&param_lit)?;
+                        control = Some(if ident == "nrpn" {
+                            MidiControlAttr::Nrpn(param)
+                        } else {
+                            MidiControlAttr::Rpn(param)
+                        });
+                    }
+                    "pitch_bend" => {
+                        control = Some(MidiControlAttr::PitchBend);
+                    }
+                    "aftertouch" => {
+                        control = Some(MidiControlAttr::ChannelAftertouch);
+                    }
+                    "poly_aftertouch" => {
+                        let _eq: Token![=] = input.parse()?;
+                        let note_lit: Lit = input.parse()?;
+                        control = Some(MidiControlAttr::PolyAftertouch(extract_u8_from_lit(&note_lit)?));
+                    }
+                    "channel" => {
+                        let _eq: Token![=] = input.parse()?;
+                        let channel_lit: Lit = input.parse()?;
+                        channel = Some(extract_channel_from_lit(&channel_lit)?);
+                    }
+                    "device" => {
+                        let _eq: Token![=] = input.parse()?;
+                        let device_lit: syn::LitStr = input.parse()?;
+                        if device_lit.value().is_empty() {
+                            return Err(Error::new_spanned(device_lit, "Device name pattern cannot be empty"));
+                        }
+                        device = Some(device_lit.value());
+                    }
+                    "takeover" => {
+                        let _eq: Token![=] = input.parse()?;
+                        let takeover_lit: syn::LitStr = input.parse()?;
+                        takeover = Some(TakeoverAttr::parse(&takeover_lit)?);
+                    }
+                    "relative" => {
+                        let _eq: Token![=] = input.parse()?;
+                        let mode_lit: syn::LitStr = input.parse()?;
+                        relative_mode = Some(RelativeModeAttr::parse(&mode_lit)?);
+                    }
+                    "step" | "acceleration" => {
+                        let _eq: Token![=] = input.parse()?;
+                        let value_lit: Lit = input.parse()?;
+                        let value = extract_f32_from_lit(&value_lit)?;
+                        if ident == "step" {
+                            if value <= 0.0 {
+                                return Err(Error::new_spanned(value_lit, "'step' must be greater than 0"));
+                            }
+                            step = Some((ident, value));
+                        } else {
+                            acceleration = Some((ident, value));
+                        }
+                    }
+                    "curve" => {
+                        let _eq: Token![=] = input.parse()?;
+                        curve_name = Some(input.parse::<syn::LitStr>()?);
+                    }
+                    "power" => {
+                        let _eq: Token![=] = input.parse()?;
+                        let power_lit: Lit = input.parse()?;
+                        let value = extract_f32_from_lit(&power_lit)?;
+                        if value <= 0.0 {
+                            return Err(Error::new_spanned(power_lit, "'power' must be greater than 0"));
+                        }
+                        power = Some((ident, value));
+                    }
+                    "curve_fn" => {
+                        let _eq: Token![=] = input.parse()?;
+                        curve = Some((ident, CurveAttr::Function(input.parse()?)));
+                    }
+                    "curve_table" => {
+                        let _eq: Token![=] = input.parse()?;
+                        let content;
+                        syn::bracketed!(content in input);
+                        let points = content
+                            .parse_terminated(<Lit as syn::parse::Parse>::parse, Token![,])?
+                            .iter()
+                            .map(extract_f32_from_lit)
+                            .collect::<SynResult<Vec<f32>>>()?;
+                        if points.len() < 2 {
+                            return Err(Error::new_spanned(ident, "'curve_table' needs at least 2 points"));
+                        }
+                        curve = Some((ident, CurveAttr::Table(points)));
+                    }
+                    "smooth" => {
+                        let _eq: Token![=] = input.parse()?;
+                        let seconds_lit: Lit = input.parse()?;
+                        let seconds = extract_f32_from_lit(&seconds_lit)?;
+                        if seconds < 0.0 {
+                            return Err(Error::new_spanned(seconds_lit, "'smooth' cannot be negative"));
+                        }
+                        smooth = Some(seconds);
+                    }
+                    "persist" => {
+                        persist_only = true;
+                        control = None;
+                    }
+                    "button" | "toggle" => {
+                        control_type = Some(ControlType::Button);
+                    }
+                    "momentary" => {
+                        control_type = Some(ControlType::Momentary);
+                    }
+                    "trigger" => {
+                        control_type = Some(ControlType::Trigger);
+                    }
+                    "radio" => {
+                        radio = Some(ident);
+                    }
+                    "choice" => {
+                        choice = Some(ident);
+                    }
+                    "color" => {
+                        let _eq: Token![=] = input.parse()?;
+                        let space_lit: syn::LitStr = input.parse()?;
+                        color = Some((ident, ColorSpaceAttr::parse(&space_lit)?));
+                    }
+                    "hires" => {
+                        hires = Some(ident);
+                    }
+                    _ => {
+                        return Err(Error::new_spanned(
+                            ident,
+                            "Expected 'cc', 'note', 'nrpn', 'rpn', 'pitch_bend', 'aftertouch', 'poly_aftertouch', 'channel', 'device', 'takeover', 'relative', 'step', 'acceleration', 'curve', 'power', 'curve_fn', 'curve_table', 'smooth', 'hires', 'persist', 'button', 'toggle', 'momentary', 'trigger', 'radio', 'choice', 'color' or range (e.g., 0.0..1.0)",
+                        ))
+                    }
                 }
-                "persist" => {
-                    persist_only = true;
-                    cc = None;
+            } else {
+                let lit: Lit = input.parse()?;
+
+                if input.peek(Token![..]) {
+                    // Parse range: 0.0..1.0 or 0.0..=1.0
+                    let start = extract_f32_from_lit(&lit)?;
+
+                    let _dots: Token![..] = input.parse()?;
+                    let _inclusive = input.parse::<Token![=]>().is_ok();
+
+                    let end: Lit = input.parse()?;
+                    let end = extract_f32_from_lit(&end)?;
+
+                    if start >= end {
+                        return Err(Error::new_spanned(lit, "Range start must be less than end"));
+                    }
+
+                    control_type = Some(ControlType::Range {
+                        min: start,
+                        max: end,
+                    });
+                } else if first {
+                    // First token is a number (traditional syntax)
+                    control = Some(MidiControlAttr::Cc(extract_u8_from_lit(&lit)?));
+                } else {
+                    return Err(Error::new_spanned(
+                        lit,
+                        "Only the first argument may be a bare CC number",
+                    ));
                 }
-                _ => return Err(Error::new_spanned(ident, "Expected 'cc', 'note', or 'persist'")),
             }
-        } else {
-            // First token is a number (traditional syntax)
-            let cc_lit: Lit = input.parse()?;
-            cc = Some(extract_u8_from_lit(&cc_lit)?);
+
+            first = false;
         }
 
-        if input.is_empty() {
-            // Default range for CC/persist, error for note
-            if is_note {
-                return Err(Error::new(
-                    proc_macro2::Span::call_site(),
-                    "Note mappings must specify 'button'",
-                ));
-            }
-            return Ok(MidiAttr {
-                cc,
-                control_type: ControlType::Range { min: 0.0, max: 1.0 },
-                is_note,
-                persist_only,
-            });
+        if channel.is_some() && persist_only {
+            return Err(Error::new(
+                proc_macro2::Span::call_site(),
+                "Persist-only fields cannot specify a MIDI channel",
+            ));
         }
 
-        let _comma: Token![,] = input.parse()?;
+        if device.is_some() && persist_only {
+            return Err(Error::new(
+                proc_macro2::Span::call_site(),
+                "Persist-only fields cannot specify a MIDI device",
+            ));
+        }
 
-        if input.peek(syn::Ident) {
-            let ident: syn::Ident = input.parse()?;
-            if ident == "button" {
-                control_type = Some(ControlType::Button);
-            } else {
+        if let Some(choice) = choice {
+            let cycle = match control_type {
+                // Pads step through the variants, knobs sweep through them
+                None => matches!(control, Some(MidiControlAttr::Note(_))),
+                Some(ControlType::Button) => true,
+                Some(_) => {
+                    return Err(Error::new_spanned(
+                        choice,
+                        "'choice' can only be combined with 'button' (cycle through the variants)",
+                    ))
+                }
+            };
+            control_type = Some(ControlType::Choice { cycle });
+        }
+
+        match (radio, group) {
+            (Some(radio), Some((_, controls))) => {
+                if controls.len() < 2 {
+                    return Err(Error::new_spanned(radio, "'radio' needs at least 2 buttons"));
+                }
+                if control_type.is_some() || hires.is_some() {
+                    return Err(Error::new_spanned(
+                        radio,
+                        "'radio' cannot be combined with a range, 'hires' or another button type",
+                    ));
+                }
+                control_type = Some(ControlType::Radio(controls));
+            }
+            (Some(radio), None) => {
                 return Err(Error::new_spanned(
-                    ident,
-                    "Expected 'button' or range (e.g., 0.0..1.0)",
-                ));
+                    radio,
+                    "'radio' needs a list of buttons, e.g. note = [36, 37, 38]",
+                ))
             }
-        } else {
-            // Parse range: 0.0..1.0 or 0.0..=1.0
-            if is_note {
-                return Err(Error::new(
-                    proc_macro2::Span::call_site(),
-                    "Note mappings can only be buttons",
-                ));
+            (None, Some((ident, controls))) => {
+                // One control per component
+                let range = match control_type {
+                    Some(ControlType::Range { min, max }) => Some((min, max)),
+                    None => None,
+                    Some(_) => {
+                        return Err(Error::new_spanned(
+                            ident,
+                            "A list of controls drives components or a 'radio' group, not buttons",
+                        ))
+                    }
+                };
+                if let Some(hires) = &hires {
+                    return Err(Error::new_spanned(
+                        hires,
+                        "'hires' cannot be combined with a list of controls",
+                    ));
+                }
+                if let (Some((color, _)), Some(_)) = (&color, range) {
+                    return Err(Error::new_spanned(
+                        color,
+                        "Colors use the ranges of their color space, 'color' cannot be combined with a range",
+                    ));
+                }
+                control_type = Some(ControlType::Components {
+                    controls,
+                    range,
+                    space: color.take().map(|(_, space)| space).unwrap_or_default(),
+                });
             }
+            (None, None) => {}
+        }
 
-            let start: Lit = input.parse()?;
-            let start = extract_f32_from_lit(&start)?;
+        if let Some((color, _)) = color {
+            return Err(Error::new_spanned(
+                color,
+                "'color' needs one control per component, e.g. cc = [1, 2, 3]",
+            ));
+        }
 
-            let _dots: Token![..] = input.parse()?;
-            let _inclusive = input.parse::<Token![=]>().is_ok();
+        if matches!(control_type, Some(ControlType::Trigger)) && control.is_none() {
+            return Err(Error::new(
+                proc_macro2::Span::call_site(),
+                "Triggers need a MIDI control, they cannot be persist-only",
+            ));
+        }
 
-            let end: Lit = input.parse()?;
-            let end = extract_f32_from_lit(&end)?;
+        if let Some(hires) = hires {
+            control = match control {
+                Some(MidiControlAttr::Cc(cc)) if cc < 32 => Some(MidiControlAttr::HiResCc(cc)),
+                _ => {
+                    return Err(Error::new_spanned(
+                        hires,
+                        "'hires' requires a CC between 0 and 31 (its LSB is sent on CC + 32)",
+                    ))
+                }
+            };
+        }
 
-            if start >= end {
+        // Default range for CC/persist, error for note
+        let control_type = match control_type {
+            Some(control_type) => control_type,
+            None if matches!(control, Some(MidiControlAttr::Note(_))) => {
                 return Err(Error::new(
                     proc_macro2::Span::call_site(),
-                    "Range start must be less than end",
-                ));
+                    "Note mappings must specify 'button', 'momentary', 'trigger', 'choice' or a velocity range",
+                ))
             }
+            None => ControlType::Range { min: 0.0, max: 1.0 },
+        };
 
-            control_type = Some(ControlType::Range {
-                min: start,
-                max: end,
-            });
+        // Takeover reconciles an absolute control's position with a range value
+        let takes_over = control_type.is_continuous()
+            && !matches!(control, None | Some(MidiControlAttr::Note(_)));
+        if takeover.is_some() && !takes_over {
+            return Err(Error::new(
+                proc_macro2::Span::call_site(),
+                "'takeover' only applies to ranges driven by a CC, NRPN, RPN, pitch bend or aftertouch",
+            ));
+        }
+
+        let relative = match relative_mode {
+            Some(mode) => {
+                // Increments arrive as plain 7-bit CC values
+                if !matches!(control, Some(MidiControlAttr::Cc(_))) || !control_type.is_continuous()
+                {
+                    return Err(Error::new(
+                        proc_macro2::Span::call_site(),
+                        "'relative' requires a CC mapped to a range",
+                    ));
+                }
+                if takeover.is_some() {
+                    return Err(Error::new(
+                        proc_macro2::Span::call_site(),
+                        "Relative encoders never jump, 'takeover' cannot be combined with 'relative'",
+                    ));
+                }
+                Some(RelativeAttr {
+                    mode,
+                    step: step.map(|(_, step)| step),
+                    acceleration: acceleration.map(|(_, acceleration)| acceleration),
+                })
+            }
+            None => {
+                if let Some((ident, _)) = step.or(acceleration) {
+                    return Err(Error::new_spanned(
+                        ident,
+                        "'step' and 'acceleration' require 'relative'",
+                    ));
+                }
+                None
+            }
+        };
+
+        let curve = match (curve_name, curve) {
+            (Some(name), Some((ident, _))) => {
+                return Err(Error::new_spanned(
+                    ident,
+                    format!("'curve = \"{}\"' and a custom curve cannot be combined", name.value()),
+                ))
+            }
+            (Some(name), None) => Some(CurveAttr::parse(&name, power.map(|(_, power)| power))?),
+            (None, custom) => {
+                if let Some((ident, _)) = power {
+                    return Err(Error::new_spanned(ident, "'power' requires 'curve = \"exponential\"'"));
+                }
+                custom.map(|(_, curve)| curve)
+            }
+        };
+        if curve.is_some() && !control_type.is_continuous() {
+            return Err(Error::new(
+                proc_macro2::Span::call_site(),
+                "Response curves only apply to ranges",
+            ));
+        }
+        if curve.is_some() && relative.is_some() {
+            return Err(Error::new(
+                proc_macro2::Span::call_site(),
+                "Relative encoders step in field units, 'curve' cannot be combined with 'relative'",
+            ));
         }
 
-        let control_type = control_type
-            .ok_or_else(|| Error::new(proc_macro2::Span::call_site(), "Missing control type"))?;
+        if smooth.is_some()
+            && (control.is_none() || !control_type.is_continuous())
+        {
+            return Err(Error::new(
+                proc_macro2::Span::call_site(),
+                "'smooth' only applies to ranges driven by MIDI",
+            ));
+        }
 
         Ok(MidiAttr {
-            cc,
+            control,
+            channel,
+            device,
+            takeover,
+            relative,
+            curve,
+            smooth,
             control_type,
-            is_note,
-            persist_only,
         })
     }
 }
@@ -384,6 +1470,32 @@ fn extract_u8_from_lit(lit: &Lit) -> SynResult<u8> {
     }
 }
 
+fn extract_u14_from_lit(lit: &Lit) -> SynResult<u16> {
+    match lit {
+        Lit::Int(int) => {
+            let val = int.base10_parse::<u16>()?;
+            if val > 16383 {
+                return Err(Error::new_spanned(int, "NRPN/RPN number must be 0-16383"));
+            }
+            Ok(val)
+        }
+        _ => Err(Error::new_spanned(lit, "Expected integer for NRPN/RPN number")),
+    }
+}
+
+fn extract_channel_from_lit(lit: &Lit) -> SynResult<u8> {
+    match lit {
+        Lit::Int(int) => {
+            let val = int.base10_parse::<u8>()?;
+            if val > 15 {
+                return Err(Error::new_spanned(int, "MIDI channel must be 0-15"));
+            }
+            Ok(val)
+        }
+        _ => Err(Error::new_spanned(lit, "Expected integer for MIDI channel")),
+    }
+}
+
 fn extract_f32_from_lit(lit: &Lit) -> SynResult<f32> {
     match lit {
         Lit::Float(f) => f.base10_parse::<f32>(),